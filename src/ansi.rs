@@ -0,0 +1,320 @@
+use macroquad::prelude::Color;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AnsiStyle {
+    pub foreground: Option<Color>,
+    pub bold: bool,
+    pub underline: bool,
+}
+
+impl AnsiStyle {
+    const PALETTE: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 49, 49),
+        (13, 188, 121),
+        (229, 229, 16),
+        (36, 114, 200),
+        (188, 63, 188),
+        (17, 168, 205),
+        (229, 229, 229),
+        (102, 102, 102),
+        (241, 76, 76),
+        (35, 209, 139),
+        (245, 245, 67),
+        (59, 142, 234),
+        (214, 112, 214),
+        (41, 184, 219),
+        (255, 255, 255),
+    ];
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    /// Applies the parameters of an SGR (`ESC [ ... m`) sequence.
+    pub fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Self::default();
+            return;
+        }
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                4 => self.underline = true,
+                24 => self.underline = false,
+                30..=37 => self.foreground = Some(Self::palette_color(param - 30)),
+                90..=97 => self.foreground = Some(Self::palette_color(param - 90 + 8)),
+                39 => self.foreground = None,
+                38 => self.foreground = Self::extended_color(&mut params),
+                48 => {
+                    Self::extended_color(&mut params);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Reads a 256 color (`5;n`) or true color (`2;r;g;b`) argument.
+    fn extended_color(params: &mut impl Iterator<Item = u16>) -> Option<Color> {
+        match params.next() {
+            Some(5) => params.next().map(Self::indexed_color),
+            Some(2) => {
+                let r = params.next()?;
+                let g = params.next()?;
+                let b = params.next()?;
+                Some(Color::from_rgba(r as u8, g as u8, b as u8, 255))
+            }
+            _ => None,
+        }
+    }
+
+    fn palette_color(index: u16) -> Color {
+        let (r, g, b) = Self::PALETTE[index as usize % Self::PALETTE.len()];
+        Color::from_rgba(r, g, b, 255)
+    }
+
+    fn indexed_color(index: u16) -> Color {
+        match index {
+            0..=15 => Self::palette_color(index),
+            16..=231 => {
+                let index = index - 16;
+                Color::from_rgba(
+                    Self::CUBE_LEVELS[(index / 36) as usize],
+                    Self::CUBE_LEVELS[(index / 6 % 6) as usize],
+                    Self::CUBE_LEVELS[(index % 6) as usize],
+                    255,
+                )
+            }
+            _ => {
+                let gray = (8 + 10 * (index.min(255) - 232)) as u8;
+                Color::from_rgba(gray, gray, gray, 255)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnsiSegment {
+    pub text: String,
+    pub style: AnsiStyle,
+}
+
+/// Splits terminal output into lines of styled segments, the way a terminal
+/// would show it. Carriage returns move back to the start of the line, so
+/// progress output only keeps the last update. Output can be read in chunks
+/// as it arrives, also when a chunk ends in the middle of an escape sequence.
+#[derive(Default)]
+pub struct AnsiText {
+    lines: Vec<Vec<AnsiSegment>>,
+    line: Vec<(char, AnsiStyle)>,
+    cursor: usize,
+    style: AnsiStyle,
+    pending: String,
+}
+
+impl AnsiText {
+    const ESCAPE: char = '\u{1b}';
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(text: &str) -> Vec<Vec<AnsiSegment>> {
+        let mut ansi_text = Self::new();
+        ansi_text.push(text);
+        if !ansi_text.line.is_empty() {
+            ansi_text.new_line();
+        }
+        ansi_text.lines
+    }
+
+    /// Reads more output. An unfinished escape sequence at the end is kept
+    /// until the rest of it arrives.
+    pub fn push(&mut self, text: &str) {
+        let text = std::mem::take(&mut self.pending) + text;
+        self.read(&text);
+    }
+
+    /// The last lines read, ending with the line being written to, which
+    /// may be empty.
+    pub fn last_lines(&self, count: usize) -> Vec<Vec<AnsiSegment>> {
+        let skipped = (self.lines.len() + 1).saturating_sub(count);
+        self.lines
+            .iter()
+            .skip(skipped)
+            .cloned()
+            .chain(std::iter::once(Self::segments(&self.line)))
+            .collect()
+    }
+
+    fn read(&mut self, text: &str) {
+        let mut chars = text.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                Self::ESCAPE => match chars.next() {
+                    Some((_, '[')) => {
+                        let mut sequence = String::new();
+                        let mut finished = false;
+                        for (_, c) in chars.by_ref() {
+                            if ('\u{40}'..='\u{7e}').contains(&c) {
+                                self.control_sequence(&sequence, c);
+                                finished = true;
+                                break;
+                            }
+                            sequence.push(c);
+                        }
+                        if !finished {
+                            self.pending = text[index..].to_string();
+                        }
+                    }
+                    Some(_) => (),
+                    None => self.pending = text[index..].to_string(),
+                },
+                '\r' => match chars.peek() {
+                    Some((_, '\n')) => (),
+                    Some(_) => self.cursor = 0,
+                    None => self.pending = c.to_string(),
+                },
+                '\n' => self.new_line(),
+                '\u{8}' => self.cursor = self.cursor.saturating_sub(1),
+                '\t' => self.write(c),
+                c if c.is_control() => (),
+                c => self.write(c),
+            }
+        }
+    }
+
+    fn control_sequence(&mut self, sequence: &str, command: char) {
+        match command {
+            'm' => {
+                let params = sequence
+                    .split(';')
+                    .filter(|param| !param.is_empty())
+                    .map(|param| param.parse().unwrap_or(0))
+                    .collect::<Vec<u16>>();
+                self.style.apply_sgr(&params);
+            }
+            'K' => self.line.truncate(self.cursor),
+            'G' => self.cursor = sequence.parse::<usize>().unwrap_or(1).saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    fn write(&mut self, c: char) {
+        while self.line.len() < self.cursor {
+            self.line.push((' ', AnsiStyle::default()));
+        }
+        if self.cursor < self.line.len() {
+            self.line[self.cursor] = (c, self.style);
+        } else {
+            self.line.push((c, self.style));
+        }
+        self.cursor += 1;
+    }
+
+    fn new_line(&mut self) {
+        let segments = Self::segments(&self.line);
+        self.lines.push(segments);
+        self.line.clear();
+        self.cursor = 0;
+    }
+
+    /// Joins characters with the same style into segments.
+    fn segments(line: &[(char, AnsiStyle)]) -> Vec<AnsiSegment> {
+        let mut segments: Vec<AnsiSegment> = vec![];
+        for (c, style) in line.iter().copied() {
+            match segments.last_mut() {
+                Some(segment) if segment.style == style => segment.text.push(c),
+                _ => segments.push(AnsiSegment {
+                    text: c.to_string(),
+                    style,
+                }),
+            }
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[Vec<AnsiSegment>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|segments| {
+                segments
+                    .iter()
+                    .map(|segment| segment.text.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_lines() {
+        assert_eq!(texts(&AnsiText::parse("one\ntwo\n")), vec!["one", "two"]);
+        assert_eq!(texts(&AnsiText::parse("one\r\ntwo")), vec!["one", "two"]);
+    }
+
+    #[test]
+    fn carriage_return_overwrites_the_line() {
+        assert_eq!(texts(&AnsiText::parse("10%\r50%\r100%\n")), vec!["100%"]);
+        assert_eq!(texts(&AnsiText::parse("abc\rX")), vec!["Xbc"]);
+        assert_eq!(texts(&AnsiText::parse("abc\r\x1b[Kx")), vec!["x"]);
+    }
+
+    #[test]
+    fn styles_segments() {
+        let lines = AnsiText::parse("\x1b[1;32mok\x1b[0m done");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].len(), 2);
+        assert_eq!(lines[0][0].text, "ok");
+        assert!(lines[0][0].style.bold);
+        assert_eq!(
+            lines[0][0].style.foreground,
+            Some(Color::from_rgba(13, 188, 121, 255))
+        );
+        assert_eq!(lines[0][1].text, " done");
+        assert_eq!(lines[0][1].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn reads_extended_colors() {
+        let mut style = AnsiStyle::default();
+        style.apply_sgr(&[38, 2, 1, 2, 3]);
+        assert_eq!(style.foreground, Some(Color::from_rgba(1, 2, 3, 255)));
+        style.apply_sgr(&[38, 5, 196]);
+        assert_eq!(style.foreground, Some(Color::from_rgba(255, 0, 0, 255)));
+        style.apply_sgr(&[39]);
+        assert_eq!(style.foreground, None);
+    }
+
+    #[test]
+    fn keeps_escape_sequences_split_between_chunks() {
+        let mut ansi_text = AnsiText::new();
+        ansi_text.push("a\x1b[3");
+        ansi_text.push("1mb\n");
+        let lines = ansi_text.last_lines(10);
+        assert_eq!(texts(&lines), vec!["ab", ""]);
+        assert_eq!(
+            lines[0][1].style.foreground,
+            Some(AnsiStyle::palette_color(1))
+        );
+    }
+
+    #[test]
+    fn keeps_line_breaks_split_between_chunks() {
+        let mut ansi_text = AnsiText::new();
+        ansi_text.push("a\r");
+        ansi_text.push("\nb");
+        assert_eq!(texts(&ansi_text.last_lines(10)), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn last_lines_ends_with_the_current_line() {
+        let mut ansi_text = AnsiText::new();
+        ansi_text.push("1\n2\n3\n4");
+        assert_eq!(texts(&ansi_text.last_lines(2)), vec!["3", "4"]);
+    }
+}
@@ -31,9 +31,41 @@ pub struct AppOptions {
     /// Enable executing code in code blocks
     #[arg(long)]
     pub enable_code_execution: bool,
+    /// Run all code blocks without showing the slides, and report failures
+    #[arg(long)]
+    pub check_code: bool,
+    /// Run all code blocks without showing the slides, and save the outputs
+    #[arg(long)]
+    pub record_code: bool,
+    /// File with commands used to run code blocks
+    #[arg(long, default_value = "runners.json")]
+    pub runners: PathBuf,
+    /// Stop executing code after N seconds, 0 means no timeout
+    #[arg(long, value_name = "SECONDS", default_value = "10")]
+    pub code_timeout: Duration,
     /// Path to directory where application files are loaded from
     #[arg(short = 'A', long, default_value = "assets")]
     pub assets: PathBuf,
+    /// Directory in assets with custom syntax definitions and code themes
+    #[arg(long, default_value = "syntaxes")]
+    pub syntaxes: PathBuf,
+    /// Show the presenter view with notes, next slide and timer, following
+    /// other instances started with --sync
+    #[arg(long)]
+    pub presenter: bool,
+    /// Show the current slide, notes, next slide and timer in the terminal,
+    /// and move between slides with keys typed there
+    #[arg(long)]
+    pub presenter_console: bool,
+    /// Follow the current slide of other instances showing the same slides
+    #[arg(long)]
+    pub sync: bool,
+    /// Length of the talk in minutes, shown by the talk timer
+    #[arg(long, value_name = "MINUTES")]
+    pub talk_length: Option<Duration>,
+    /// File with key bindings
+    #[arg(short, long, default_value = "keymap.json")]
+    pub keymap: PathBuf,
     /// Slide number to start at
     #[arg(short = 'n', long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
     pub number: u32,
@@ -51,4 +83,33 @@ impl AppOptions {
         path.push(self.theme.clone());
         path
     }
+
+    pub fn keymap_path(&self) -> PathBuf {
+        let mut path = self.directory.clone();
+        path.push(self.keymap.clone());
+        path
+    }
+
+    pub fn syntaxes_path(&self) -> PathBuf {
+        let mut path = self.assets.clone();
+        path.push(self.syntaxes.clone());
+        path
+    }
+
+    /// Recorded code outputs are stored next to the slides file.
+    pub fn recording_path(&self) -> PathBuf {
+        self.slides_path().with_extension("outputs.json")
+    }
+
+    /// The current slide is shared with other instances in a file next to the
+    /// slides file.
+    pub fn sync_path(&self) -> PathBuf {
+        self.slides_path().with_extension("position")
+    }
+
+    pub fn runners_path(&self) -> PathBuf {
+        let mut path = self.directory.clone();
+        path.push(self.runners.clone());
+        path
+    }
 }
@@ -0,0 +1,64 @@
+use crate::prelude::Duration;
+use macroquad::prelude::*;
+
+/// Fades the screen to a single color, to get the attention of the audience
+/// away from the slides.
+pub struct BlankScreen {
+    color: Color,
+    blanked: bool,
+    opacity: f32,
+}
+
+impl Default for BlankScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlankScreen {
+    const FADE_TIME: Duration = 0.3;
+
+    pub fn new() -> Self {
+        Self {
+            color: BLACK,
+            blanked: false,
+            opacity: 0.,
+        }
+    }
+
+    /// Blanks the screen with the color, or shows the slide again if the
+    /// screen is already blanked with that color.
+    pub fn toggle(&mut self, color: Color) {
+        if self.blanked && self.color == color {
+            self.restore();
+        } else {
+            self.color = color;
+            self.blanked = true;
+        }
+    }
+
+    pub fn restore(&mut self) {
+        self.blanked = false;
+    }
+
+    pub fn is_blanked(&self) -> bool {
+        self.blanked
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        let step = delta / Self::FADE_TIME;
+        self.opacity = if self.blanked {
+            (self.opacity + step).min(1.)
+        } else {
+            (self.opacity - step).max(0.)
+        };
+    }
+
+    pub fn draw(&self) {
+        if self.opacity > 0. {
+            let mut color = self.color;
+            color.a = self.opacity;
+            draw_rectangle(0., 0., screen_width(), screen_height(), color);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use nanoserde::DeJson;
+use regex::Regex;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::prelude::ExecutionError;
+
+/// Settings for building Rust code blocks with dependencies as cargo projects.
+#[derive(Clone, Debug, DeJson)]
+#[nserde(default)]
+pub struct CargoSettings {
+    /// Only use crates from the local registry cache and vendored paths
+    pub offline: bool,
+    /// Manifest used for code blocks without their own front matter
+    pub manifest: Option<String>,
+    /// Directory relative paths are resolved from, defaults to the
+    /// slideshow directory
+    pub directory: String,
+}
+
+impl Default for CargoSettings {
+    fn default() -> Self {
+        Self {
+            offline: true,
+            manifest: None,
+            directory: String::new(),
+        }
+    }
+}
+
+impl CargoSettings {
+    fn path(&self, path: &str) -> PathBuf {
+        Path::new(&self.directory).join(path)
+    }
+
+    fn deck_manifest(&self) -> Option<String> {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| fs::read_to_string(self.path(manifest)).ok())
+    }
+
+    /// Cargo config from the slideshow directory, used for vendored sources.
+    pub fn config_path(&self) -> Option<PathBuf> {
+        Some(self.path(".cargo/config.toml")).filter(|path| path.is_file())
+    }
+}
+
+/// A scratch cargo project in the cache directory. There is one project per
+/// manifest, and all projects share a target directory, so that warm builds
+/// are reused between runs.
+pub struct CargoProject {
+    pub dir: PathBuf,
+    pub target_dir: PathBuf,
+    name: String,
+}
+
+impl CargoProject {
+    /// Returns the manifest and the Rust code, if the code has front matter
+    /// or the slideshow has a manifest for code blocks.
+    pub fn manifest(settings: &CargoSettings, code: &str) -> Option<(String, String)> {
+        Self::split_front_matter(code)
+            .or_else(|| Self::split_doc_comment_manifest(code))
+            .or_else(|| {
+                settings
+                    .deck_manifest()
+                    .map(|manifest| (manifest, code.to_string()))
+            })
+    }
+
+    /// Front matter as in cargo script, between two `---` lines.
+    fn split_front_matter(code: &str) -> Option<(String, String)> {
+        let rest = code.strip_prefix("---")?;
+        let (info, rest) = rest.split_once('\n')?;
+        if !info.trim().is_empty() && info.trim() != "cargo" {
+            return None;
+        }
+        let mut manifest = String::new();
+        let mut lines = rest.split_inclusive('\n');
+        for line in lines.by_ref() {
+            if line.trim_end() == "---" {
+                return Some((manifest, lines.collect()));
+            }
+            manifest.push_str(line);
+        }
+        None
+    }
+
+    /// Manifest in a doc comment as in rust-script, i.e. lines starting with
+    /// `//!` in a code block marked as `cargo`.
+    fn split_doc_comment_manifest(code: &str) -> Option<(String, String)> {
+        let mut lines = code
+            .lines()
+            .skip_while(|line| line.trim().is_empty())
+            .map_while(|line| line.trim_start().strip_prefix("//!"));
+        lines.find(|line| line.trim() == "```cargo")?;
+        let mut manifest = String::new();
+        for line in lines {
+            if line.trim() == "```" {
+                return Some((manifest, code.to_string()));
+            }
+            manifest.push_str(line.strip_prefix(' ').unwrap_or(line));
+            manifest.push('\n');
+        }
+        None
+    }
+
+    pub fn create(
+        settings: &CargoSettings,
+        manifest: &str,
+        code: &str,
+    ) -> Result<Self, ExecutionError> {
+        let manifest = Self::resolve_paths(settings, manifest);
+        let name = format!("slide-{:016x}", Self::hash(&manifest));
+        let cache_dir = Self::cache_dir();
+        let project = Self {
+            dir: cache_dir.join(&name),
+            target_dir: cache_dir.join("target"),
+            name,
+        };
+        let cargo_toml = format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n\n{}",
+            project.name, manifest
+        );
+        project.write_if_changed(&project.dir.join("Cargo.toml"), &cargo_toml)?;
+        project.write_if_changed(&project.dir.join("src").join("main.rs"), code)?;
+        Ok(project)
+    }
+
+    /// FNV-1a hash, which unlike the hasher in std stays the same between
+    /// Rust releases, so the cached projects are found again.
+    fn hash(text: &str) -> u64 {
+        text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.dir.join("Cargo.toml")
+    }
+
+    pub fn binary(&self) -> PathBuf {
+        self.target_dir
+            .join("debug")
+            .join(format!("{}{}", self.name, env::consts::EXE_SUFFIX))
+    }
+
+    /// Only writes files that have changed, so cargo doesn't rebuild
+    /// unchanged code.
+    fn write_if_changed(&self, path: &Path, contents: &str) -> Result<(), ExecutionError> {
+        if fs::read_to_string(path).is_ok_and(|current| current == contents) {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| ExecutionError::CreateTempFile(err.to_string()))?;
+        }
+        fs::write(path, contents).map_err(|err| ExecutionError::CreateTempFile(err.to_string()))
+    }
+
+    /// Makes relative `path` dependencies relative to the slideshow directory.
+    fn resolve_paths(settings: &CargoSettings, manifest: &str) -> String {
+        let re = Regex::new(r#"path\s*=\s*"([^"]+)""#).unwrap();
+        re.replace_all(manifest, |captures: &regex::Captures| {
+            if Path::new(&captures[1]).is_absolute() {
+                return captures[0].to_string();
+            }
+            let path = settings.path(&captures[1]);
+            format!("path = {:?}", path.canonicalize().unwrap_or(path))
+        })
+        .to_string()
+    }
+
+    fn cache_dir() -> PathBuf {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .unwrap_or_else(env::temp_dir)
+            .join("rusty-slider")
+            .join("cargo")
+    }
+}
@@ -0,0 +1,193 @@
+use crate::prelude::*;
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+
+#[derive(Copy, Clone, Debug, DeJson)]
+#[allow(non_camel_case_types)]
+pub enum VerticalPosition {
+    top,
+    bottom,
+}
+
+impl Default for VerticalPosition {
+    fn default() -> Self {
+        VerticalPosition::bottom
+    }
+}
+
+#[derive(Copy, Clone, Debug, DeJson)]
+#[allow(non_camel_case_types)]
+pub enum Corner {
+    top_left,
+    top_right,
+    bottom_left,
+    bottom_right,
+}
+
+impl Default for Corner {
+    fn default() -> Self {
+        Corner::bottom_right
+    }
+}
+
+/// Chrome elements hidden on a slide with a `<!-- hide_chrome -->` comment.
+/// Without a list of elements, all of them are hidden.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct HiddenChrome {
+    pub progress_bar: bool,
+    pub slide_counter: bool,
+    pub deck_title: bool,
+}
+
+impl HiddenChrome {
+    pub fn parse(elements: &str) -> Self {
+        if elements.trim().is_empty() {
+            return Self {
+                progress_bar: true,
+                slide_counter: true,
+                deck_title: true,
+            };
+        }
+        let mut hidden = Self::default();
+        for element in elements.split(|c: char| c == ',' || c.is_whitespace()) {
+            match element {
+                "progress_bar" => hidden.progress_bar = true,
+                "slide_counter" => hidden.slide_counter = true,
+                "deck_title" => hidden.deck_title = true,
+                _ => (),
+            }
+        }
+        hidden
+    }
+}
+
+/// Progress bar, slide counter and deck title drawn on top of the slides, as
+/// set in the theme.
+#[derive(Clone)]
+pub struct Chrome {
+    theme: Theme,
+    font: Font,
+    deck_title: Option<String>,
+}
+
+impl Chrome {
+    const MARGIN: f32 = 20.;
+
+    pub fn new(theme: Theme, font: Font, deck_title: Option<String>) -> Self {
+        Self {
+            theme,
+            font,
+            deck_title,
+        }
+    }
+
+    pub fn draw(&self, slide_number: usize, slide_count: usize, hidden: HiddenChrome) {
+        if self.theme.progress_bar && !hidden.progress_bar {
+            self.draw_progress_bar(slide_number, slide_count);
+        }
+        if self.theme.slide_counter && !hidden.slide_counter {
+            self.draw_slide_counter(slide_number, slide_count);
+        }
+        if self.theme.deck_title && !hidden.deck_title {
+            self.draw_deck_title();
+        }
+    }
+
+    fn draw_progress_bar(&self, slide_number: usize, slide_count: usize) {
+        let thickness = self.theme.progress_bar_thickness;
+        let vpos = match self.theme.progress_bar_position {
+            VerticalPosition::top => 0.,
+            VerticalPosition::bottom => screen_height() - thickness,
+        };
+        let width = screen_width() * slide_number as f32 / slide_count.max(1) as f32;
+        draw_rectangle(0., vpos, width, thickness, self.theme.progress_bar_color);
+    }
+
+    fn draw_slide_counter(&self, slide_number: usize, slide_count: usize) {
+        let text = format!("{} / {}", slide_number, slide_count);
+        let dimensions = self.measure(&text);
+        let hpos = match self.theme.slide_counter_position {
+            Corner::top_left | Corner::bottom_left => Self::MARGIN,
+            Corner::top_right | Corner::bottom_right => {
+                screen_width() - dimensions.width - Self::MARGIN
+            }
+        };
+        let vpos = match self.theme.slide_counter_position {
+            Corner::top_left | Corner::top_right => Self::MARGIN + dimensions.offset_y,
+            Corner::bottom_left | Corner::bottom_right => {
+                screen_height() - Self::MARGIN - (dimensions.height - dimensions.offset_y)
+            }
+        };
+        self.draw_text(&text, hpos, vpos);
+    }
+
+    fn draw_deck_title(&self) {
+        let Some(deck_title) = &self.deck_title else {
+            return;
+        };
+        let dimensions = self.measure(deck_title);
+        self.draw_text(
+            deck_title,
+            screen_width() / 2. - dimensions.width / 2.,
+            screen_height() - Self::MARGIN - (dimensions.height - dimensions.offset_y),
+        );
+    }
+
+    fn measure(&self, text: &str) -> TextDimensions {
+        measure_text(text, Some(&self.font), self.theme.chrome_font_size, 1.)
+    }
+
+    fn draw_text(&self, text: &str, hpos: Hpos, vpos: Vpos) {
+        draw_text_ex(
+            text,
+            hpos,
+            vpos,
+            TextParams {
+                font: Some(&self.font),
+                font_size: self.theme.chrome_font_size,
+                color: self.theme.chrome_text_color,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hides_all_chrome_without_elements() {
+        let all = HiddenChrome {
+            progress_bar: true,
+            slide_counter: true,
+            deck_title: true,
+        };
+        assert_eq!(HiddenChrome::parse(""), all);
+        assert_eq!(HiddenChrome::parse("  \n"), all);
+    }
+
+    #[test]
+    fn hides_listed_elements() {
+        assert_eq!(
+            HiddenChrome::parse("progress_bar, deck_title"),
+            HiddenChrome {
+                progress_bar: true,
+                slide_counter: false,
+                deck_title: true,
+            }
+        );
+        assert_eq!(
+            HiddenChrome::parse("slide_counter"),
+            HiddenChrome {
+                slide_counter: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_unknown_elements() {
+        assert_eq!(HiddenChrome::parse("clock"), HiddenChrome::default());
+    }
+}
@@ -0,0 +1,54 @@
+use crate::prelude::*;
+
+/// How the last run of a code block ended. It is kept so that all of the
+/// output can be scrolled through, not only the lines that fit in the box.
+#[derive(Clone)]
+pub enum FinishedRun {
+    Result(ExecutionResult),
+    Error { output: String, status: String },
+}
+
+/// An executable code block on a slide, with the output from the last time
+/// it was run.
+#[derive(Clone)]
+pub struct CodeBlock {
+    pub draw_box_index: usize,
+    pub code: ExecutableCode,
+    pub output: Option<CodeBox>,
+    pub finished: Option<FinishedRun>,
+    pub scroll: usize,
+    pub running: bool,
+}
+
+impl CodeBlock {
+    pub fn new(draw_box_index: usize, code: ExecutableCode) -> Self {
+        Self {
+            draw_box_index,
+            code,
+            output: None,
+            finished: None,
+            scroll: 0,
+            running: false,
+        }
+    }
+
+    /// Removes output from earlier runs.
+    pub fn start(&mut self) {
+        self.running = true;
+        self.clear_output();
+    }
+
+    /// Keeps the run, with the box showing the end of its output.
+    pub fn finish(&mut self, finished: FinishedRun, output: CodeBox) {
+        self.running = false;
+        self.finished = Some(finished);
+        self.scroll = 0;
+        self.output = Some(output);
+    }
+
+    pub fn clear_output(&mut self) {
+        self.output = None;
+        self.finished = None;
+        self.scroll = 0;
+    }
+}
@@ -1,40 +1,159 @@
 use crate::prelude::*;
-use macroquad::prelude::{Color, Font};
+use macroquad::prelude::{Color, Font, WHITE};
+use std::{fmt, path::Path};
 use syntect::easy::HighlightLines;
 use syntect::highlighting::FontStyle;
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
+use syntect::highlighting::{Style, Theme as SyntectTheme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
 
+#[derive(Debug)]
+pub enum HighlightingError {
+    LoadSyntaxes(String, String),
+    LoadThemes(String, String),
+    UnknownCodeTheme(String, Vec<String>),
+}
+
+impl fmt::Display for HighlightingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HighlightingError::LoadSyntaxes(path, message) => {
+                write!(
+                    f,
+                    "Couldn't load syntax definitions from {}: {}",
+                    path, message
+                )
+            }
+            HighlightingError::LoadThemes(path, message) => {
+                write!(f, "Couldn't load code themes from {}: {}", path, message)
+            }
+            HighlightingError::UnknownCodeTheme(name, available) => write!(
+                f,
+                "Unknown code theme: {}\nAvailable code themes: {}",
+                name,
+                available.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HighlightingError {}
+
+/// A line of a diff code block.
+#[derive(Debug, PartialEq)]
+enum DiffLine<'a> {
+    Added(&'a str),
+    Removed(&'a str),
+    Unchanged(&'a str),
+    /// A `@@` hunk header, or a `---` or `+++` file header before the first
+    /// hunk
+    Header(&'a str),
+    Other(&'a str),
+}
+
+impl<'a> DiffLine<'a> {
+    fn parse(code: &'a str) -> Vec<DiffLine<'a>> {
+        let mut in_hunk = false;
+        LinesWithEndings::from(code)
+            .map(|line| {
+                if line.starts_with("@@") {
+                    in_hunk = true;
+                    return DiffLine::Header(line);
+                }
+                if !in_hunk && (line.starts_with("--- ") || line.starts_with("+++ ")) {
+                    return DiffLine::Header(line);
+                }
+                match line.chars().next() {
+                    Some('+') => DiffLine::Added(&line[1..]),
+                    Some('-') => DiffLine::Removed(&line[1..]),
+                    Some(' ') => DiffLine::Unchanged(&line[1..]),
+                    _ => DiffLine::Other(line),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone)]
 pub struct CodeBoxBuilder {
     ps: SyntaxSet,
-    ts: ThemeSet,
     font_text: Font,
     font_bold: Font,
     font_italic: Font,
     font_size: FontSize,
     line_height: Height,
     background_color: Color,
+    diff_added_background_color: Color,
+    diff_removed_background_color: Color,
+    output_background_color: Color,
+    output_text_color: Color,
+    output_max_lines: usize,
+    output_error_color: Color,
+    output_failure_color: Color,
     tab_spaces: String,
-    highlighting_theme: String,
+    highlighting_theme: SyntectTheme,
     margin: Height,
 }
 
 impl CodeBoxBuilder {
-    pub fn new(theme: Theme, font_text: Font, font_bold: Font, font_italic: Font) -> Self {
-        Self {
-            ts: ThemeSet::load_defaults(),
-            ps: SyntaxSet::load_defaults_newlines(),
+    /// Custom `.sublime-syntax` and `.tmTheme` files are loaded from
+    /// `syntaxes_path` if that directory exists.
+    pub fn new(
+        theme: Theme,
+        syntaxes_path: &Path,
+        font_text: Font,
+        font_bold: Font,
+        font_italic: Font,
+    ) -> Result<Self, HighlightingError> {
+        let ps = Self::load_syntaxes(syntaxes_path)?;
+        let mut ts = Self::load_themes(syntaxes_path)?;
+        let Some(highlighting_theme) = ts.themes.remove(&theme.code_theme) else {
+            return Err(HighlightingError::UnknownCodeTheme(
+                theme.code_theme,
+                ts.themes.keys().cloned().collect(),
+            ));
+        };
+        Ok(Self {
+            ps,
             font_text,
             font_bold,
             font_italic,
             font_size: theme.font_code_size.to_owned(),
             line_height: theme.code_line_height.to_owned(),
             background_color: theme.code_background_color.to_owned(),
+            diff_added_background_color: theme.diff_added_background_color.to_owned(),
+            diff_removed_background_color: theme.diff_removed_background_color.to_owned(),
+            output_background_color: theme.output_background_color.to_owned(),
+            output_text_color: theme.output_text_color.to_owned(),
+            output_max_lines: theme.output_max_lines,
+            output_error_color: theme.output_error_color.to_owned(),
+            output_failure_color: theme.output_failure_color.to_owned(),
             tab_spaces: " ".repeat(theme.code_tab_width),
-            highlighting_theme: theme.code_theme.to_owned(),
+            highlighting_theme,
             margin: 10.0,
+        })
+    }
+
+    fn load_syntaxes(path: &Path) -> Result<SyntaxSet, HighlightingError> {
+        let syntaxes = SyntaxSet::load_defaults_newlines();
+        if !path.is_dir() {
+            return Ok(syntaxes);
+        }
+        let mut builder = syntaxes.into_builder();
+        builder.add_from_folder(path, true).map_err(|err| {
+            HighlightingError::LoadSyntaxes(path.to_string_lossy().to_string(), err.to_string())
+        })?;
+        Ok(builder.build())
+    }
+
+    fn load_themes(path: &Path) -> Result<ThemeSet, HighlightingError> {
+        let mut themes = ThemeSet::load_defaults();
+        if path.is_dir() {
+            themes.add_from_folder(path).map_err(|err| {
+                HighlightingError::LoadThemes(path.to_string_lossy().to_string(), err.to_string())
+            })?;
         }
+        Ok(themes)
     }
 
     pub fn build_draw_box(&self, language: Option<String>, code: String) -> CodeBox {
@@ -50,47 +169,331 @@ impl CodeBoxBuilder {
         )
     }
 
+    /// Builds a terminal styled box for the output of running code, with
+    /// ANSI colors, bold and underline applied.
+    pub fn build_console_box(&self, lines: &[Vec<AnsiSegment>]) -> CodeBox {
+        self.build_terminal_box(
+            self.build_segment_lines(lines, self.output_text_color),
+            self.output_background_color,
+        )
+    }
+
+    /// Number of lines of output shown in a code box.
+    pub fn output_max_lines(&self) -> usize {
+        self.output_max_lines
+    }
+
+    /// Shows the screen of a terminal, all lines are shown.
+    pub fn build_screen_box(&self, screen: &TerminalScreen, show_cursor: bool) -> CodeBox {
+        self.build_terminal_box(
+            self.build_segment_lines(&screen.lines(show_cursor), self.output_text_color),
+            self.output_background_color,
+        )
+    }
+
+    /// Shows stdout and stderr from the code, or the output received before
+    /// an error, followed by a status line. The output is shown from the
+    /// end, scrolled up the number of lines given. Failed runs have a
+    /// different title bar and status color.
+    pub fn build_finished_box(&self, finished: &FinishedRun, scroll: usize) -> CodeBox {
+        let (stdout, stderr) = Self::finished_lines(finished);
+        let (status, success) = match finished {
+            FinishedRun::Result(result) => (Self::result_status(result), result.success()),
+            FinishedRun::Error { status, .. } => (status.clone(), false),
+        };
+        let count = stdout.len() + stderr.len();
+        let end = count - scroll.min(count.saturating_sub(self.output_max_lines));
+        let start = end.saturating_sub(self.output_max_lines);
+        let split = stdout.len();
+        let mut text_lines = self.build_segment_lines(
+            &stdout[start.min(split)..end.min(split)],
+            self.output_text_color,
+        );
+        text_lines.extend(self.build_segment_lines(
+            &stderr[start.max(split) - split..end.max(split) - split],
+            self.output_error_color,
+        ));
+        let status = if count > self.output_max_lines {
+            format!("{}, lines {}-{} of {}", status, start + 1, end, count)
+        } else {
+            status
+        };
+        text_lines.push(self.build_status_line(&status, success));
+        self.build_terminal_box(text_lines, self.result_title_color(success))
+    }
+
+    /// How many lines the output of a finished run can be scrolled up.
+    pub fn max_scroll(&self, finished: &FinishedRun) -> usize {
+        let (stdout, stderr) = Self::finished_lines(finished);
+        (stdout.len() + stderr.len()).saturating_sub(self.output_max_lines)
+    }
+
+    /// Status for a run that ended with an error, shown after its output.
+    pub fn error_status(error: &ExecutionError, elapsed: std::time::Duration) -> String {
+        let status = match error {
+            ExecutionError::Compile(_) => "Compile error".to_string(),
+            error => error.to_string(),
+        };
+        format!("{} after {:.2}s", status, elapsed.as_secs_f32())
+    }
+
+    fn result_status(result: &ExecutionResult) -> String {
+        let duration = result.duration.as_secs_f32();
+        match (result.success(), result.exit_code) {
+            (true, _) => format!("Finished in {:.2}s", duration),
+            (false, Some(exit_code)) => {
+                format!("Failed with exit code {} in {:.2}s", exit_code, duration)
+            }
+            (false, None) => format!("Killed after {:.2}s", duration),
+        }
+    }
+
+    /// Lines of stdout and stderr of a finished run.
+    fn finished_lines(finished: &FinishedRun) -> (Vec<Vec<AnsiSegment>>, Vec<Vec<AnsiSegment>>) {
+        match finished {
+            FinishedRun::Result(result) => (
+                AnsiText::parse(&result.stdout),
+                AnsiText::parse(&result.stderr),
+            ),
+            FinishedRun::Error { output, .. } => (AnsiText::parse(output.trim()), vec![]),
+        }
+    }
+
+    fn result_title_color(&self, success: bool) -> Color {
+        if success {
+            self.output_background_color
+        } else {
+            self.output_failure_color
+        }
+    }
+
+    fn build_status_line(&self, status: &str, success: bool) -> TextLine {
+        let mut status_color = if success {
+            self.output_text_color
+        } else {
+            self.output_error_color
+        };
+        status_color.a *= 0.8;
+        TextLine::new(
+            DrawAlignment::left,
+            vec![TextPartial::new(
+                status,
+                self.font_italic.clone(),
+                self.font_size,
+                status_color,
+                self.line_height,
+            )],
+        )
+    }
+
+    fn build_terminal_box(&self, text_lines: Vec<TextLine>, title_color: Color) -> CodeBox {
+        CodeBox::new(
+            TextBox::new(
+                text_lines,
+                0.,
+                Some(self.output_background_color),
+                TextBoxStyle::Code,
+            ),
+            self.margin,
+            Some(title_color),
+        )
+    }
+
+    fn build_segment_lines(&self, lines: &[Vec<AnsiSegment>], text_color: Color) -> Vec<TextLine> {
+        lines
+            .iter()
+            .map(|segments| {
+                let mut partials = vec![];
+                for segment in segments {
+                    let font = if segment.style.bold {
+                        self.font_bold.clone()
+                    } else {
+                        self.font_text.clone()
+                    };
+                    let mut partial = TextPartial::new(
+                        &segment.text.replace('\t', &self.tab_spaces),
+                        font,
+                        self.font_size,
+                        segment.style.foreground.unwrap_or(text_color),
+                        self.line_height,
+                    );
+                    partial.set_underline(segment.style.underline);
+                    partials.push(partial);
+                }
+                if partials.is_empty() {
+                    partials.push(TextPartial::new(
+                        " ",
+                        self.font_text.clone(),
+                        self.font_size,
+                        text_color,
+                        self.line_height,
+                    ));
+                }
+                TextLine::new(DrawAlignment::left, partials)
+            })
+            .collect()
+    }
+
     fn build_text_lines(&self, language: Option<String>, code: String) -> Vec<TextLine> {
-        let syntax = match language {
-            Some(lang) => self.ps.find_syntax_by_token(&lang),
-            None => self.ps.find_syntax_by_first_line(&code),
+        match language.as_deref().and_then(Self::diff_language) {
+            Some(diff_language) => self.build_diff_lines(diff_language, &code),
+            None => self.build_highlighted_lines(language.as_deref(), &code),
         }
-        .unwrap_or_else(|| self.ps.find_syntax_plain_text());
-        let theme = &self.ts.themes[&self.highlighting_theme];
-        let mut h = HighlightLines::new(syntax, theme);
-        let lines = LinesWithEndings::from(&code)
+    }
+
+    /// Returns the underlying language of a diff code block, i.e. `rust` for
+    /// `diff-rust`, or `Some(None)` for a plain `diff` block.
+    fn diff_language(language: &str) -> Option<Option<&str>> {
+        match language {
+            "diff" => Some(None),
+            language => language.strip_prefix("diff-").map(Some),
+        }
+    }
+
+    fn build_highlighted_lines(&self, language: Option<&str>, code: &str) -> Vec<TextLine> {
+        let mut h = HighlightLines::new(self.find_syntax(language, code), &self.highlighting_theme);
+        LinesWithEndings::from(code)
             .map(|line| h.highlight_line(line, &self.ps))
             .filter_map(Result::ok)
-            .collect::<Vec<_>>();
+            .map(|tokens| TextLine::new(DrawAlignment::left, self.build_partials(&tokens)))
+            .collect()
+    }
+
+    /// Lines starting with `+` or `-` get a background color, while the rest
+    /// of each line is highlighted using the underlying language. File and
+    /// hunk headers are shown as they are.
+    fn build_diff_lines(&self, language: Option<&str>, code: &str) -> Vec<TextLine> {
+        let syntax = match language {
+            Some(language) => self.find_syntax(Some(language), code),
+            None => self.ps.find_syntax_plain_text(),
+        };
+        let theme = &self.highlighting_theme;
+        let marker_color = theme
+            .settings
+            .foreground
+            .map(|c| Color::from_rgba(c.r, c.g, c.b, c.a))
+            .unwrap_or(WHITE);
+        let mut h = HighlightLines::new(syntax, theme);
 
         let mut text_lines = vec![];
-        let mut partials = vec![];
-        for tokens in lines.iter() {
-            for (style, text) in tokens {
-                let text = text.trim_end_matches('\n').replace('\t', &self.tab_spaces);
-                if text.is_empty() {
+        for line in DiffLine::parse(code) {
+            let (marker, content, background_color) = match line {
+                DiffLine::Added(content) => ("+", content, Some(self.diff_added_background_color)),
+                DiffLine::Removed(content) => {
+                    ("-", content, Some(self.diff_removed_background_color))
+                }
+                DiffLine::Unchanged(content) => (" ", content, None),
+                DiffLine::Header(header) => {
+                    text_lines.push(TextLine::new(
+                        DrawAlignment::left,
+                        vec![TextPartial::new(
+                            header.trim_end_matches(['\r', '\n']),
+                            self.font_text.clone(),
+                            self.font_size,
+                            marker_color,
+                            self.line_height,
+                        )],
+                    ));
                     continue;
                 }
-
-                let c = style.foreground;
-                let font_style = match style.font_style {
-                    FontStyle::BOLD => self.font_bold.clone(),
-                    FontStyle::ITALIC => self.font_italic.clone(),
-                    _ => self.font_text.clone(),
-                };
-
+                DiffLine::Other(content) => ("", content, None),
+            };
+            let mut partials = vec![];
+            if !marker.is_empty() {
                 partials.push(TextPartial::new(
-                    &text,
-                    font_style,
+                    marker,
+                    self.font_text.clone(),
                     self.font_size,
-                    Color::from_rgba(c.r, c.g, c.b, c.a),
+                    marker_color,
                     self.line_height,
                 ));
             }
-            text_lines.push(TextLine::new(DrawAlignment::left, partials));
-            partials = Vec::new();
+            if let Ok(tokens) = h.highlight_line(content, &self.ps) {
+                partials.extend(self.build_partials(&tokens));
+            }
+            let mut text_line = TextLine::new(DrawAlignment::left, partials);
+            if let Some(color) = background_color {
+                text_line.set_background_color(color);
+            }
+            text_lines.push(text_line);
         }
-
         text_lines
     }
+
+    fn find_syntax(&self, language: Option<&str>, code: &str) -> &SyntaxReference {
+        match language {
+            Some(lang) => self.ps.find_syntax_by_token(lang),
+            None => self.ps.find_syntax_by_first_line(code),
+        }
+        .unwrap_or_else(|| self.ps.find_syntax_plain_text())
+    }
+
+    fn build_partials(&self, tokens: &[(Style, &str)]) -> Vec<TextPartial> {
+        let mut partials = vec![];
+        for (style, text) in tokens {
+            let text = text.trim_end_matches('\n').replace('\t', &self.tab_spaces);
+            if text.is_empty() {
+                continue;
+            }
+
+            let c = style.foreground;
+            let font_style = match style.font_style {
+                FontStyle::BOLD => self.font_bold.clone(),
+                FontStyle::ITALIC => self.font_italic.clone(),
+                _ => self.font_text.clone(),
+            };
+
+            partials.push(TextPartial::new(
+                &text,
+                font_style,
+                self.font_size,
+                Color::from_rgba(c.r, c.g, c.b, c.a),
+                self.line_height,
+            ));
+        }
+        partials
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_language_of_diff() {
+        assert_eq!(CodeBoxBuilder::diff_language("diff"), Some(None));
+        assert_eq!(
+            CodeBoxBuilder::diff_language("diff-rust"),
+            Some(Some("rust"))
+        );
+        assert_eq!(CodeBoxBuilder::diff_language("rust"), None);
+        assert_eq!(CodeBoxBuilder::diff_language("difference"), None);
+    }
+
+    #[test]
+    fn parses_diff_lines() {
+        assert_eq!(
+            DiffLine::parse("+added\n-removed\n kept\nother"),
+            vec![
+                DiffLine::Added("added\n"),
+                DiffLine::Removed("removed\n"),
+                DiffLine::Unchanged("kept\n"),
+                DiffLine::Other("other"),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_and_hunk_headers_are_not_changes() {
+        assert_eq!(
+            DiffLine::parse("--- a/main.rs\n+++ b/main.rs\n@@ -1 +1 @@\n--- x\n+++ y\n"),
+            vec![
+                DiffLine::Header("--- a/main.rs\n"),
+                DiffLine::Header("+++ b/main.rs\n"),
+                DiffLine::Header("@@ -1 +1 @@\n"),
+                DiffLine::Removed("-- x\n"),
+                DiffLine::Added("++ y\n"),
+            ]
+        );
+    }
 }
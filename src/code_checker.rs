@@ -0,0 +1,325 @@
+use crate::prelude::*;
+use markdown::Block;
+use std::{fmt, fs, time};
+
+#[derive(Debug, PartialEq)]
+pub enum CheckResult {
+    Passed,
+    Failed(String),
+}
+
+/// The result of running one code block in the slideshow.
+pub struct CodeCheck {
+    pub slide_number: usize,
+    pub language: String,
+    pub result: CheckResult,
+}
+
+impl CodeCheck {
+    pub fn passed(&self) -> bool {
+        matches!(self.result, CheckResult::Passed)
+    }
+}
+
+impl fmt::Display for CodeCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.result {
+            CheckResult::Passed => {
+                write!(f, "ok      slide {} ({})", self.slide_number, self.language)
+            }
+            CheckResult::Failed(reason) => write!(
+                f,
+                "FAILED  slide {} ({})\n{}",
+                self.slide_number,
+                self.language,
+                reason
+                    .lines()
+                    .map(|line| format!("        {}", line))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+        }
+    }
+}
+
+/// A runnable code block in the slideshow, with the expected output if there
+/// is one.
+struct DeckCode {
+    slide_number: usize,
+    code: ExecutableCode,
+    expected: Option<String>,
+}
+
+/// Runs every code block with a runner in a slideshow without opening a
+/// window. A code block with the language `output` directly after a code
+/// block is the expected output of that block.
+pub struct CodeChecker {
+    runners: Runners,
+    timeout: Option<time::Duration>,
+}
+
+impl CodeChecker {
+    const EXPECTED_OUTPUT_LANGUAGE: &'static str = "output";
+
+    pub fn new(runners: Runners, timeout: Duration) -> Self {
+        Self {
+            runners,
+            timeout: (timeout > 0.).then(|| time::Duration::from_secs_f32(timeout)),
+        }
+    }
+
+    pub fn load(options: &AppOptions) -> Self {
+        let runners_path = options.runners_path();
+        let runners = Runners::from_json(fs::read_to_string(&runners_path).ok(), &runners_path);
+        Self::new(runners, options.code_timeout)
+    }
+
+    /// Checks all code blocks in the slides file, prints a report and returns
+    /// the exit code for the program.
+    pub fn run(&self, options: &AppOptions) -> i32 {
+        let Some(markdown) = Self::read_markdown(options) else {
+            return 1;
+        };
+        let checks = self
+            .find_code_blocks(&markdown)
+            .into_iter()
+            .map(|deck_code| CodeCheck {
+                slide_number: deck_code.slide_number,
+                language: deck_code.code.language().to_string(),
+                result: self.check_code(&deck_code.code, deck_code.expected.as_deref()),
+            })
+            .collect::<Vec<CodeCheck>>();
+        for check in checks.iter() {
+            println!("{}", check);
+        }
+        let failed = checks.iter().filter(|check| !check.passed()).count();
+        println!(
+            "\n{} code blocks checked: {} passed, {} failed",
+            checks.len(),
+            checks.len() - failed,
+            failed
+        );
+        if failed > 0 { 1 } else { 0 }
+    }
+
+    /// Runs all code blocks in the slides file and saves the outputs next to
+    /// it, so they can be shown without running the code. Returns the exit
+    /// code for the program.
+    pub fn record(&self, options: &AppOptions) -> i32 {
+        let Some(markdown) = Self::read_markdown(options) else {
+            return 1;
+        };
+        let mut recorded_outputs = RecordedOutputs::default();
+        let mut failed = 0;
+        for deck_code in self.find_code_blocks(&markdown) {
+            match deck_code
+                .code
+                .execute_with(&ExecutionControl::new(self.timeout))
+            {
+                Ok(result) => {
+                    println!(
+                        "recorded  slide {} ({})",
+                        deck_code.slide_number,
+                        deck_code.code.language()
+                    );
+                    recorded_outputs.add(RecordedOutput::new(&deck_code.code, &result));
+                }
+                Err(err) => {
+                    println!(
+                        "FAILED    slide {} ({}): {}",
+                        deck_code.slide_number,
+                        deck_code.code.language(),
+                        err
+                    );
+                    failed += 1;
+                }
+            }
+        }
+        let path = options.recording_path();
+        if let Err(err) = recorded_outputs.save(&path) {
+            eprintln!("Couldn't save recorded outputs to {:?}: {}", path, err);
+            return 2;
+        }
+        println!(
+            "\n{} code outputs saved to {:?}",
+            recorded_outputs.outputs.len(),
+            path
+        );
+        if failed > 0 { 1 } else { 0 }
+    }
+
+    fn read_markdown(options: &AppOptions) -> Option<String> {
+        let path = options.slides_path();
+        match fs::read_to_string(&path) {
+            Ok(text) => Some(Slides::sanitize_markdown(text).0),
+            Err(_) => {
+                eprintln!("Couldn't parse markdown document: {:?}", path);
+                None
+            }
+        }
+    }
+
+    /// Slides are split the same way as in the slideshow.
+    fn find_code_blocks(&self, markdown: &str) -> Vec<DeckCode> {
+        let mut deck_codes = vec![];
+        for (index, blocks) in MarkdownToSlides::split_slides(markdown).iter().enumerate() {
+            self.find_in_blocks(index + 1, blocks, &mut deck_codes);
+        }
+        deck_codes
+    }
+
+    fn find_in_blocks(
+        &self,
+        slide_number: usize,
+        blocks: &[Block],
+        deck_codes: &mut Vec<DeckCode>,
+    ) {
+        for (index, block) in blocks.iter().enumerate() {
+            match block {
+                Block::Blockquote(blocks) => self.find_in_blocks(slide_number, blocks, deck_codes),
+                Block::CodeBlock(Some(language), code) if self.runners.find(language).is_some() => {
+                    let expected = match blocks.get(index + 1) {
+                        Some(Block::CodeBlock(Some(language), expected))
+                            if language == Self::EXPECTED_OUTPUT_LANGUAGE =>
+                        {
+                            Some(expected.to_owned())
+                        }
+                        _ => None,
+                    };
+                    deck_codes.push(DeckCode {
+                        slide_number,
+                        code: ExecutableCode::from(language, code, &self.runners),
+                        expected,
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn check_code(&self, code: &ExecutableCode, expected: Option<&str>) -> CheckResult {
+        match code.execute_with(&ExecutionControl::new(self.timeout)) {
+            Ok(result) => Self::check_result(&result, expected),
+            Err(err) => CheckResult::Failed(err.to_string()),
+        }
+    }
+
+    /// The code passes if it exits successfully, and its standard output
+    /// matches the expected output when there is one. Trailing whitespace is
+    /// ignored when comparing.
+    fn check_result(result: &ExecutionResult, expected: Option<&str>) -> CheckResult {
+        if !result.success() {
+            return CheckResult::Failed(format!(
+                "{}\n{}",
+                match result.exit_code {
+                    Some(exit_code) => format!("Exited with code {}", exit_code),
+                    None => "Killed by signal".to_string(),
+                },
+                result.stderr.trim_end()
+            ));
+        }
+        match expected {
+            Some(expected) if Self::normalize(expected) != Self::normalize(&result.stdout) => {
+                CheckResult::Failed(format!(
+                    "Expected output:\n{}\nActual output:\n{}",
+                    expected.trim_end(),
+                    result.stdout.trim_end()
+                ))
+            }
+            _ => CheckResult::Passed,
+        }
+    }
+
+    fn normalize(output: &str) -> String {
+        output
+            .trim_end()
+            .lines()
+            .map(|line| line.trim_end())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(stdout: &str, exit_code: Option<i32>) -> ExecutionResult {
+        ExecutionResult {
+            stdout: stdout.to_string(),
+            stderr: "error: oops\n".to_string(),
+            exit_code,
+            duration: time::Duration::from_millis(10),
+        }
+    }
+
+    fn code_block(language: &str, code: &str) -> Block {
+        Block::CodeBlock(Some(language.to_string()), code.to_string())
+    }
+
+    #[test]
+    fn ignores_trailing_whitespace() {
+        assert_eq!(
+            CodeChecker::normalize("one  \ntwo\t\n\n\n"),
+            CodeChecker::normalize("one\ntwo")
+        );
+        assert_ne!(
+            CodeChecker::normalize("  one\ntwo"),
+            CodeChecker::normalize("one\ntwo")
+        );
+    }
+
+    #[test]
+    fn passes_when_output_matches() {
+        let result = result("hello \nworld\n\n", Some(0));
+        assert_eq!(
+            CodeChecker::check_result(&result, Some("hello\nworld\n")),
+            CheckResult::Passed
+        );
+        assert_eq!(
+            CodeChecker::check_result(&result, None),
+            CheckResult::Passed
+        );
+    }
+
+    #[test]
+    fn fails_when_output_differs() {
+        let result = result("hello\n", Some(0));
+        assert_eq!(
+            CodeChecker::check_result(&result, Some("goodbye\n")),
+            CheckResult::Failed("Expected output:\ngoodbye\nActual output:\nhello".to_string())
+        );
+    }
+
+    #[test]
+    fn fails_on_error_exit() {
+        assert_eq!(
+            CodeChecker::check_result(&result("", Some(3)), None),
+            CheckResult::Failed("Exited with code 3\nerror: oops".to_string())
+        );
+        assert_eq!(
+            CodeChecker::check_result(&result("", None), None),
+            CheckResult::Failed("Killed by signal\nerror: oops".to_string())
+        );
+    }
+
+    #[test]
+    fn finds_expected_output_after_code() {
+        let checker = CodeChecker::new(Runners::default(), 0.);
+        let blocks = [
+            code_block("bash", "echo one"),
+            code_block("output", "one"),
+            code_block("cobol", "DISPLAY 'two'"),
+            Block::Blockquote(vec![code_block("python", "print(3)")]),
+            code_block("output", "3"),
+        ];
+        let mut deck_codes = vec![];
+        checker.find_in_blocks(2, &blocks, &mut deck_codes);
+        assert_eq!(deck_codes.len(), 2);
+        assert_eq!(deck_codes[0].slide_number, 2);
+        assert_eq!(deck_codes[0].code.language(), "bash");
+        assert_eq!(deck_codes[0].expected.as_deref(), Some("one"));
+        assert_eq!(deck_codes[1].code.language(), "python");
+        assert_eq!(deck_codes[1].expected, None);
+    }
+}
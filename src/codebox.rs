@@ -22,6 +22,12 @@ impl CodeBox {
     const CHROME_OUTLINE_YELLOW: Color = Color::new(0.863, 0.592, 0.110, 1.);
     const CHROME_COLOR_GREEN: Color = Color::new(0.157, 0.784, 0.251, 1.);
     const CHROME_OUTLINE_GREEN: Color = Color::new(0.106, 0.639, 0.153, 1.);
+    const OUTLINE_DISTANCE: f32 = 4.;
+    const OUTLINE_THICKNESS: f32 = 3.;
+    const SPINNER_COLOR: Color = Color::new(0.5, 0.5, 0.5, 1.);
+    const SPINNER_DOTS: usize = 8;
+    const SPINNER_RADIUS: f32 = 8.;
+    const SPINNER_DOT_RADIUS: f32 = 2.;
 
     pub fn new(textbox: TextBox, margin: Height, background_color: Option<Color>) -> Self {
         Self {
@@ -66,6 +72,42 @@ impl CodeBox {
         new_vpos + self.margin
     }
 
+    pub fn draw_outline(&self, hpos: Hpos, vpos: Vpos, color: Color) {
+        draw_rectangle_lines(
+            hpos - Self::OUTLINE_DISTANCE,
+            vpos + self.margin - Self::OUTLINE_DISTANCE,
+            self.width + Self::CHROME_CORNER_RADIUS + Self::OUTLINE_DISTANCE * 2.,
+            self.height
+                + Self::TITLE_BAR_HEIGHT
+                + Self::CHROME_CORNER_RADIUS
+                + Self::OUTLINE_DISTANCE * 2.,
+            Self::OUTLINE_THICKNESS,
+            color,
+        );
+    }
+
+    /// Draws a spinning indicator in the title bar, to show that the code is
+    /// running.
+    pub fn draw_spinner(&self, hpos: Hpos, vpos: Vpos) {
+        let center_x = hpos + self.width + Self::CHROME_CORNER_RADIUS
+            - Self::CHROME_CIRCLE_DISTANCE
+            - Self::SPINNER_RADIUS;
+        let center_y = vpos + self.margin + Self::TITLE_BAR_HEIGHT / 2.;
+        let step = (get_time() * Self::SPINNER_DOTS as f64 * 1.5) as usize;
+        for dot in 0..Self::SPINNER_DOTS {
+            let angle = dot as f32 / Self::SPINNER_DOTS as f32 * std::f32::consts::TAU;
+            let age = (step + Self::SPINNER_DOTS - dot) % Self::SPINNER_DOTS;
+            let mut color = Self::SPINNER_COLOR;
+            color.a = 1. - age as f32 / Self::SPINNER_DOTS as f32;
+            draw_circle(
+                center_x + angle.cos() * Self::SPINNER_RADIUS,
+                center_y + angle.sin() * Self::SPINNER_RADIUS,
+                Self::SPINNER_DOT_RADIUS,
+                color,
+            );
+        }
+    }
+
     fn draw_rounded_rectangle(
         &self,
         hpos: Hpos,
@@ -131,6 +173,14 @@ impl CodeBox {
         );
     }
 
+    pub fn highlight(&mut self, query: &str, color: Color) -> usize {
+        self.textbox.highlight(query, color)
+    }
+
+    pub fn select_highlight(&mut self, index: Option<usize>) -> Option<usize> {
+        self.textbox.select_highlight(index)
+    }
+
     pub fn width(&self) -> Width {
         self.width
     }
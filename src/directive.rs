@@ -0,0 +1,169 @@
+use crate::prelude::{Duration, HiddenChrome};
+use regex::Regex;
+
+/// Something set for a slide in a comment on it: speaker notes in
+/// `<!-- notes: ... -->`, the chrome to hide in `<!-- hide_chrome -->` and a
+/// time budget in `<!-- time_budget: 2 -->`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Directive {
+    Notes(String),
+    HideChrome(HiddenChrome),
+    /// In seconds, the comment gives it in minutes
+    TimeBudget(Option<Duration>),
+}
+
+impl Directive {
+    /// Language of the code blocks left where the directives were.
+    pub const LANGUAGE: &'static str = "directive";
+
+    fn parse(name: &str, value: &str) -> Self {
+        let value = value.trim();
+        match name {
+            "notes" => Directive::Notes(value.to_string()),
+            "hide_chrome" => Directive::HideChrome(HiddenChrome::parse(value)),
+            _ => Directive::TimeBudget(value.parse::<Duration>().ok().map(|minutes| minutes * 60.)),
+        }
+    }
+
+    /// Removes the comments from the markdown, except in fenced code blocks.
+    /// Each directive is replaced with a code block holding its index in the
+    /// returned list, so the markdown parser keeps it on its slide whatever
+    /// text it contains.
+    pub fn extract(text: &str) -> (String, Vec<Directive>) {
+        let comment = Regex::new(r"(?s)\A<!--(.*?)--\s*>").unwrap();
+        let directive = Regex::new(r"(?s)\A\s*(notes|hide_chrome|time_budget)\b:?(.*)\z").unwrap();
+        let mut markdown = String::new();
+        let mut directives = vec![];
+        let mut in_fence = false;
+        let mut line_start = true;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let line_end = rest.find('\n').map_or(rest.len(), |end| end + 1);
+            let line = &rest[..line_end];
+            if line_start {
+                let fence = if in_fence {
+                    Self::closes_fence(line)
+                } else {
+                    line.trim_start().starts_with("```")
+                };
+                if in_fence || fence {
+                    in_fence = in_fence != fence;
+                    markdown.push_str(line);
+                    rest = &rest[line_end..];
+                    continue;
+                }
+            }
+            let Some(start) = line.find("<!--") else {
+                markdown.push_str(line);
+                rest = &rest[line_end..];
+                line_start = true;
+                continue;
+            };
+            markdown.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match comment.captures(rest) {
+                Some(captures) => {
+                    if let Some(found) = directive.captures(&captures[1]) {
+                        markdown.push_str(&format!(
+                            "\n\n```{}\n{}\n```\n\n",
+                            Self::LANGUAGE,
+                            directives.len()
+                        ));
+                        directives.push(Self::parse(&found[1], &found[2]));
+                    }
+                    rest = &rest[captures[0].len()..];
+                }
+                None => {
+                    markdown.push_str("<!--");
+                    rest = &rest[4..];
+                }
+            }
+            line_start = false;
+        }
+        (markdown, directives)
+    }
+
+    fn closes_fence(line: &str) -> bool {
+        let line = line.trim();
+        line.len() >= 3 && line.chars().all(|c| c == '`')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(index: usize) -> String {
+        format!("\n\n```directive\n{}\n```\n\n", index)
+    }
+
+    #[test]
+    fn extracts_directives() {
+        let (markdown, directives) = Directive::extract(
+            "# One\n<!-- notes: Say hello -->\n<!-- hide_chrome: progress_bar -->\n<!-- time_budget: 2 -->\n",
+        );
+        assert_eq!(
+            markdown,
+            format!("# One\n{}\n{}\n{}\n", marker(0), marker(1), marker(2))
+        );
+        assert_eq!(
+            directives,
+            vec![
+                Directive::Notes("Say hello".to_string()),
+                Directive::HideChrome(HiddenChrome {
+                    progress_bar: true,
+                    ..Default::default()
+                }),
+                Directive::TimeBudget(Some(120.)),
+            ]
+        );
+    }
+
+    #[test]
+    fn hides_all_chrome_without_elements() {
+        let (_, directives) = Directive::extract("<!-- hide_chrome -->");
+        assert_eq!(
+            directives,
+            vec![Directive::HideChrome(HiddenChrome::parse(""))]
+        );
+    }
+
+    #[test]
+    fn keeps_line_breaks_and_backticks_in_notes() {
+        let (markdown, directives) =
+            Directive::extract("<!-- notes:\nline one\n```\nline `two`\n-->");
+        assert_eq!(markdown, marker(0));
+        assert_eq!(
+            directives,
+            vec![Directive::Notes("line one\n```\nline `two`".to_string())]
+        );
+    }
+
+    #[test]
+    fn removes_other_comments() {
+        let (markdown, directives) = Directive::extract("a <!-- todo --> b <!-- notesy -->\n");
+        assert_eq!(markdown, "a  b \n");
+        assert!(directives.is_empty());
+    }
+
+    #[test]
+    fn leaves_comments_in_fenced_code() {
+        let text = "```html\n<!-- notes: not a note -->\n<p>\n```\n<!-- notes: a note -->";
+        let (markdown, directives) = Directive::extract(text);
+        assert_eq!(
+            markdown,
+            format!(
+                "```html\n<!-- notes: not a note -->\n<p>\n```\n{}",
+                marker(0)
+            )
+        );
+        assert_eq!(directives, vec![Directive::Notes("a note".to_string())]);
+    }
+
+    #[test]
+    fn leaves_unfinished_comments() {
+        let (markdown, directives) = Directive::extract("text <!-- notes: never ends");
+        assert_eq!(markdown, "text <!-- notes: never ends");
+        assert!(directives.is_empty());
+    }
+}
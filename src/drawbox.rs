@@ -6,6 +6,7 @@ pub enum DrawBox {
     Image(ImageBox),
     Text(TextBox),
     Code(CodeBox),
+    Terminal(TerminalBox),
 }
 
 impl DrawBox {
@@ -26,6 +27,7 @@ impl DrawBox {
             }
             DrawBox::Text(_) => (),
             DrawBox::Code(_) => (),
+            DrawBox::Terminal(_) => (),
         }
     }
 
@@ -34,6 +36,28 @@ impl DrawBox {
             DrawBox::Image(image_box) => image_box.draw(hpos, vpos),
             DrawBox::Text(text_box) => text_box.draw(hpos, vpos),
             DrawBox::Code(code_box) => code_box.draw(hpos, vpos),
+            DrawBox::Terminal(terminal_box) => terminal_box.draw(hpos, vpos),
+        }
+    }
+
+    /// Highlights text matching the search query, returns the number of
+    /// matches.
+    pub fn highlight(&mut self, query: &str, color: Color) -> usize {
+        match self {
+            DrawBox::Image(_) => 0,
+            DrawBox::Text(text_box) => text_box.highlight(query, color),
+            DrawBox::Code(code_box) => code_box.highlight(query, color),
+            DrawBox::Terminal(_) => 0,
+        }
+    }
+
+    /// Marks a highlight as the current search match, see
+    /// `TextBox::select_highlight`.
+    pub fn select_highlight(&mut self, index: Option<usize>) -> Option<usize> {
+        match self {
+            DrawBox::Text(text_box) => text_box.select_highlight(index),
+            DrawBox::Code(code_box) => code_box.select_highlight(index),
+            DrawBox::Image(_) | DrawBox::Terminal(_) => index,
         }
     }
 
@@ -42,6 +66,7 @@ impl DrawBox {
             DrawBox::Image(image_box) => image_box.width_with_padding(),
             DrawBox::Text(text_box) => text_box.width_with_padding(),
             DrawBox::Code(code_box) => code_box.width_with_padding(),
+            DrawBox::Terminal(terminal_box) => terminal_box.width_with_padding(),
         }
     }
 }
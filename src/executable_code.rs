@@ -1,11 +1,22 @@
 use std::{
-    ffi::OsStr,
-    fmt,
+    fmt, fs,
     io::prelude::*,
-    process::{Command, Stdio},
+    path::Path,
+    process::{Child, ChildStdin, Command, ExitStatus, Stdio},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use tempfile::NamedTempFile;
+use tempfile::tempdir;
+
+use crate::prelude::{
+    CargoProject, CargoSettings, ExecutionLimits, OutputLimit, Runner, RunnerCommand, Runners,
+};
 
 #[derive(Debug)]
 pub enum ExecutionError {
@@ -14,6 +25,8 @@ pub enum ExecutionError {
     CreateTempFile(String),
     Compile(String),
     UnknkownLanguage(String),
+    Cancelled,
+    Timeout(Duration),
 }
 
 impl fmt::Display for ExecutionError {
@@ -28,6 +41,10 @@ impl fmt::Display for ExecutionError {
             ExecutionError::UnknkownLanguage(language) => {
                 write!(f, "Don't know how to compile {}", language)
             }
+            ExecutionError::Cancelled => write!(f, "Execution cancelled"),
+            ExecutionError::Timeout(timeout) => {
+                write!(f, "Execution timed out after {:.1}s", timeout.as_secs_f32())
+            }
         }
     }
 }
@@ -40,118 +57,447 @@ impl From<std::io::Error> for ExecutionError {
     }
 }
 
+/// Output and exit code from running the code. A process killed by a signal
+/// has no exit code.
+#[derive(Clone, Debug)]
+pub struct ExecutionResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+}
+
+impl ExecutionResult {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Stdin of the running process. Input sent before the process has started,
+/// like while the code is compiled, is kept until it can be written.
+#[derive(Default)]
+struct ProcessInput {
+    stdin: Option<ChildStdin>,
+    pending: Vec<u8>,
+    closed: bool,
+    finished: bool,
+}
+
+/// Lets a running execution be cancelled from another thread, and stops it
+/// when the timeout has passed. The timeout is for running the code, compile
+/// steps have a separate and longer limit. Output is sent to the output
+/// channel while the process is running, if there is one. Without input,
+/// the process gets an empty stdin.
+#[derive(Clone)]
+pub struct ExecutionControl {
+    cancelled: Arc<AtomicBool>,
+    started: Instant,
+    timeout: Option<Duration>,
+    output: Option<Sender<String>>,
+    input: Option<Arc<Mutex<ProcessInput>>>,
+}
+
+impl Default for ExecutionControl {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl ExecutionControl {
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// A first build with dependencies can take minutes.
+    const BUILD_TIMEOUT: Duration = Duration::from_secs(600);
+
+    const READ_BUFFER_SIZE: usize = 1024;
+
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            started: Instant::now(),
+            timeout,
+            output: None,
+            input: None,
+        }
+    }
+
+    pub fn with_output(timeout: Option<Duration>, output: Sender<String>) -> Self {
+        Self {
+            output: Some(output),
+            ..Self::new(timeout)
+        }
+    }
+
+    /// Lets text be sent to stdin of the process with `send_input`.
+    pub fn with_input(self) -> Self {
+        Self {
+            input: Some(Arc::default()),
+            ..self
+        }
+    }
+
+    /// Writes text to stdin of the process, or keeps it until the process
+    /// has started. Returns false if the process has exited or its input
+    /// has been closed.
+    pub fn send_input(&self, text: &str) -> bool {
+        let Some(Ok(mut input)) = self.input.as_ref().map(|input| input.lock()) else {
+            return false;
+        };
+        let input = &mut *input;
+        if input.closed || input.finished {
+            return false;
+        }
+        match &mut input.stdin {
+            Some(stdin) => stdin
+                .write_all(text.as_bytes())
+                .and_then(|_| stdin.flush())
+                .is_ok(),
+            None => {
+                input.pending.extend_from_slice(text.as_bytes());
+                true
+            }
+        }
+    }
+
+    /// Closes stdin of the process, which reads it as the end of input.
+    pub fn close_input(&self) {
+        if let Some(Ok(mut input)) = self.input.as_ref().map(|input| input.lock()) {
+            input.closed = true;
+            input.stdin = None;
+        }
+    }
+
+    /// Keeps stdin of the process for `send_input`, after writing the input
+    /// sent before the process started.
+    fn connect_input(&self, mut stdin: ChildStdin) {
+        if let Some(Ok(mut input)) = self.input.as_ref().map(|input| input.lock()) {
+            let pending = std::mem::take(&mut input.pending);
+            if stdin
+                .write_all(&pending)
+                .and_then(|_| stdin.flush())
+                .is_ok()
+                && !input.closed
+            {
+                input.stdin = Some(stdin);
+            }
+        }
+    }
+
+    /// Closes stdin when the process has exited, later input isn't kept.
+    fn disconnect_input(&self) {
+        if let Some(Ok(mut input)) = self.input.as_ref().map(|input| input.lock()) {
+            input.stdin = None;
+            input.finished = true;
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Waits for the process to exit, killing it if cancelled or if it has
+    /// run longer than the timeout.
+    fn wait(
+        &self,
+        process: &mut Child,
+        timeout: Option<Duration>,
+    ) -> Result<ExitStatus, ExecutionError> {
+        let started = Instant::now();
+        loop {
+            if let Some(status) = process.try_wait()? {
+                // Processes left behind by the code would keep the pipes open
+                Self::kill_process_group(process);
+                return Ok(status);
+            }
+            let error = if self.cancelled.load(Ordering::Relaxed) {
+                Some(ExecutionError::Cancelled)
+            } else {
+                timeout
+                    .filter(|timeout| started.elapsed() > *timeout)
+                    .map(ExecutionError::Timeout)
+            };
+            if let Some(error) = error {
+                Self::kill_process_group(process);
+                process.kill()?;
+                process.wait()?;
+                return Err(error);
+            }
+            thread::sleep(Self::POLL_INTERVAL);
+        }
+    }
+
+    /// Kills the process group started with the process, which includes any
+    /// processes it has started in turn.
+    #[cfg(unix)]
+    fn kill_process_group(process: &Child) {
+        unsafe { libc::kill(-(process.id() as libc::pid_t), libc::SIGKILL) };
+    }
+
+    #[cfg(not(unix))]
+    fn kill_process_group(_process: &Child) {}
+
+    /// Reads the pipe on a separate thread, so a full pipe doesn't block the
+    /// process from exiting. Each chunk read is also sent to the output
+    /// channel. Output over the limit is read but not kept.
+    fn read_in_background<R>(
+        &self,
+        pipe: Option<R>,
+        output_limit: OutputLimit,
+    ) -> thread::JoinHandle<Result<String, ExecutionError>>
+    where
+        R: Read + Send + 'static,
+    {
+        let output_sender = self.output.clone();
+        thread::spawn(move || {
+            let mut pipe = pipe.ok_or(ExecutionError::InputOutput)?;
+            let mut buffer = [0; Self::READ_BUFFER_SIZE];
+            let mut pending: Vec<u8> = vec![];
+            let mut output = String::new();
+            loop {
+                let length = pipe.read(&mut buffer)?;
+                if length > 0 {
+                    pending.extend_from_slice(&buffer[..length]);
+                }
+                // Keep incomplete UTF-8 characters until the next read
+                let valid = match std::str::from_utf8(&pending) {
+                    Err(error) if length > 0 && error.error_len().is_none() => error.valid_up_to(),
+                    _ => pending.len(),
+                };
+                let text = String::from_utf8_lossy(&pending[..valid]).to_string();
+                pending.drain(..valid);
+                if let Some(text) = output_limit.take(&text).filter(|text| !text.is_empty()) {
+                    if let Some(sender) = &output_sender {
+                        let _ = sender.send(text.clone());
+                    }
+                    output.push_str(&text);
+                }
+                if length == 0 {
+                    return Ok(output);
+                }
+            }
+        })
+    }
+}
+
 #[derive(Clone)]
-pub enum ExecutableCode {
-    Bash(String),
-    Python(String),
-    Ruby(String),
-    Perl(String),
-    Rust(String),
-    Unknown(String, String),
+pub struct ExecutableCode {
+    language: String,
+    code: String,
+    visible_code: String,
+    runner: Option<Runner>,
+    limits: ExecutionLimits,
+    cargo: CargoSettings,
 }
 
 impl fmt::Display for ExecutableCode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &*self {
-            ExecutableCode::Bash(_) => write!(f, "bash"),
-            ExecutableCode::Python(_) => write!(f, "python"),
-            ExecutableCode::Ruby(_) => write!(f, "ruby"),
-            ExecutableCode::Perl(_) => write!(f, "perl"),
-            ExecutableCode::Rust(_) => write!(f, "rust"),
-            ExecutableCode::Unknown(language, _) => write!(f, "unknown: {}", language),
+        match &self.runner {
+            Some(_) => write!(f, "{}", self.language),
+            None => write!(f, "unknown: {}", self.language),
         }
     }
 }
 
 impl ExecutableCode {
-    pub fn from(language: &str, code: &String) -> Option<Self> {
-        match language {
-            "bash" | "sh" => Some(ExecutableCode::Bash(code.to_string())),
-            "python" => Some(ExecutableCode::Python(code.to_string())),
-            "ruby" => Some(ExecutableCode::Ruby(code.to_string())),
-            "perl" => Some(ExecutableCode::Perl(code.to_string())),
-            "rust" => Some(ExecutableCode::Rust(code.to_string())),
-            language => Some(ExecutableCode::Unknown(
-                language.to_string(),
-                code.to_string(),
-            )),
+    /// Code in a language without a runner can still be focused, copied and
+    /// replayed, it just can't be run.
+    pub fn from(language: &str, code: &String, runners: &Runners) -> Self {
+        let runner = runners.find(language).cloned();
+        let (code, visible_code) = match &runner {
+            Some(runner) => (runner.executed_code(code), runner.visible_code(code)),
+            None => (code.to_string(), code.to_string()),
+        };
+        ExecutableCode {
+            language: language.to_string(),
+            code,
+            visible_code,
+            runner,
+            limits: runners.limits.clone(),
+            cargo: runners.cargo.clone(),
         }
     }
 
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// The code that is run, including hidden lines.
+    pub fn executed_code(&self) -> &str {
+        &self.code
+    }
+
+    /// The code shown on the slide, without hidden lines.
     pub fn code(&self) -> String {
-        match self {
-            ExecutableCode::Bash(code) => code.clone(),
-            ExecutableCode::Python(code) => code.clone(),
-            ExecutableCode::Ruby(code) => code.clone(),
-            ExecutableCode::Perl(code) => code.clone(),
-            ExecutableCode::Rust(code) => code.clone(),
-            ExecutableCode::Unknown(_language, code) => code.clone(),
-        }
+        self.visible_code.clone()
     }
 
-    pub fn execute(&self) -> Result<String, ExecutionError> {
-        match self {
-            ExecutableCode::Bash(code) => self.execute_command("bash", ["-"], code),
-            ExecutableCode::Python(code) => self.execute_command("python3", ["-"], code),
-            ExecutableCode::Ruby(code) => self.execute_command("ruby", ["-"], code),
-            ExecutableCode::Perl(code) => self.execute_command("perl", ["-"], code),
-            ExecutableCode::Rust(code) => self.compile_rust(code),
-            ExecutableCode::Unknown(language, _code) => {
-                Err(ExecutionError::UnknkownLanguage(language.to_string()))
+    pub fn execute(&self) -> Result<ExecutionResult, ExecutionError> {
+        self.execute_with(&ExecutionControl::default())
+    }
+
+    /// Saves the code to a file in a temporary directory, and runs it with the
+    /// runner for the language.
+    pub fn execute_with(
+        &self,
+        control: &ExecutionControl,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let started = Instant::now();
+        let runner = self
+            .runner
+            .as_ref()
+            .ok_or_else(|| ExecutionError::UnknkownLanguage(self.language.to_string()))?;
+        let dir = tempdir().map_err(|err| ExecutionError::CreateTempFile(err.to_string()))?;
+        if runner.cargo {
+            if let Some((manifest, code)) = CargoProject::manifest(&self.cargo, &self.code) {
+                return self.execute_cargo(control, &manifest, &code, dir.path(), started);
             }
         }
+        let file = dir.path().join(format!("main.{}", runner.extension));
+        fs::write(&file, &self.code)
+            .map_err(|err| ExecutionError::CreateTempFile(err.to_string()))?;
+        let binary = dir.path().join("main");
+        if let Some(compile) = &runner.compile {
+            self.compile(
+                control,
+                self.command(compile, &file, dir.path(), &binary, false),
+            )?;
+        }
+        self.run(
+            control,
+            self.command(&runner.run, &file, dir.path(), &binary, true),
+            started,
+        )
+    }
+
+    /// Builds Rust code with dependencies in a cached cargo project, and runs
+    /// the binary in the temporary directory.
+    fn execute_cargo(
+        &self,
+        control: &ExecutionControl,
+        manifest: &str,
+        code: &str,
+        dir: &Path,
+        started: Instant,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let project = CargoProject::create(&self.cargo, manifest, code)?;
+        let manifest_path = project.manifest_path().to_string_lossy().to_string();
+        let mut args = vec![
+            "build",
+            "--quiet",
+            "--manifest-path",
+            manifest_path.as_str(),
+        ];
+        if self.cargo.offline {
+            args.push("--offline");
+        }
+        let config_path = self
+            .cargo
+            .config_path()
+            .map(|path| path.to_string_lossy().to_string());
+        if let Some(config_path) = &config_path {
+            args.extend(["--config", config_path.as_str()]);
+        }
+        let binary = project.binary();
+        let mut build = self.command(
+            &RunnerCommand::new("cargo", &args),
+            &project.manifest_path(),
+            &project.dir,
+            &binary,
+            false,
+        );
+        build.env("CARGO_TARGET_DIR", &project.target_dir);
+        self.compile(control, build)?;
+        self.run(
+            control,
+            self.command(
+                &RunnerCommand::new("{binary}", &[]),
+                &project.manifest_path(),
+                dir,
+                &binary,
+                true,
+            ),
+            started,
+        )
     }
 
-    fn compile_rust(&self, code: &String) -> Result<String, ExecutionError> {
-        let temp_file = NamedTempFile::new()?;
-        let file_path = temp_file.path().to_string_lossy();
-        self.execute_command("rustc", ["-v", "-o", &file_path, "-"], code)?;
-        self.run_command_capture_output(&file_path)
+    /// Runs the command in the code directory with a scrubbed environment.
+    /// Resource limits are only used for the step that runs the code, as
+    /// compilers often need more resources than the code itself.
+    fn command(
+        &self,
+        runner_command: &RunnerCommand,
+        file: &Path,
+        dir: &Path,
+        binary: &Path,
+        limit_resources: bool,
+    ) -> Command {
+        let mut command = Command::new(runner_command.command(file, dir, binary));
+        command
+            .args(runner_command.args(file, dir, binary))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // A group of its own, so it can be killed with all of its children
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        self.limits.sandbox(&mut command, dir);
+        command.env("PYTHONUNBUFFERED", "1");
+        if limit_resources {
+            self.limits.limit_resources(&mut command);
+        }
+        command
     }
 
-    fn run_command_capture_output(&self, command: &str) -> Result<String, ExecutionError> {
-        let process = Command::new(command).stdout(Stdio::piped()).spawn()?;
-        let mut output = String::new();
-        process.stdout.unwrap().read_to_string(&mut output)?;
-        Ok(output)
+    /// Runs the code and returns the result, also when the process fails.
+    /// Only this step reads input, compile steps always get an empty stdin.
+    fn run(
+        &self,
+        control: &ExecutionControl,
+        mut command: Command,
+        started: Instant,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if control.input.is_some() {
+            command.stdin(Stdio::piped());
+        }
+        let mut process = command.spawn()?;
+        if let Some(stdin) = process.stdin.take() {
+            control.connect_input(stdin);
+        }
+        let output_limit = self.limits.output_limit();
+        let stdout = control.read_in_background(process.stdout.take(), output_limit.clone());
+        let stderr = control.read_in_background(process.stderr.take(), output_limit);
+        let status = control.wait(&mut process, control.timeout);
+        control.disconnect_input();
+        let status = status?;
+        Ok(ExecutionResult {
+            stdout: stdout.join().map_err(|_| ExecutionError::InputOutput)??,
+            stderr: stderr.join().map_err(|_| ExecutionError::InputOutput)??,
+            exit_code: status.code(),
+            duration: started.elapsed(),
+        })
     }
 
-    fn execute_command<I, S>(
+    /// Runs a compile step, which is an error if the process fails.
+    fn compile(
         &self,
-        command: &str,
-        arguments: I,
-        code: &String,
-    ) -> Result<String, ExecutionError>
-    where
-        I: IntoIterator<Item = S>,
-        S: AsRef<OsStr>,
-    {
-        let mut process = Command::new(command)
-            .args(arguments)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-        process
-            .stdin
-            .take()
-            .ok_or(ExecutionError::InputOutput)?
-            .write_all(code.as_bytes())?;
-        let mut output = String::new();
-        if process.wait()?.success() {
-            process
-                .stdout
-                .take()
-                .ok_or(ExecutionError::InputOutput)?
-                .read_to_string(&mut output)?;
-            Ok(output)
+        control: &ExecutionControl,
+        mut command: Command,
+    ) -> Result<(), ExecutionError> {
+        let mut process = command.spawn()?;
+        let output_limit = self.limits.output_limit();
+        let stdout = control.read_in_background(process.stdout.take(), output_limit.clone());
+        let stderr = control.read_in_background(process.stderr.take(), output_limit);
+        let status = control.wait(&mut process, Some(ExecutionControl::BUILD_TIMEOUT))?;
+        stdout.join().map_err(|_| ExecutionError::InputOutput)??;
+        let stderr = stderr.join().map_err(|_| ExecutionError::InputOutput)??;
+        if status.success() {
+            Ok(())
         } else {
-            process
-                .stderr
-                .take()
-                .ok_or(ExecutionError::InputOutput)?
-                .read_to_string(&mut output)?;
-            Err(ExecutionError::Compile(output))
+            Err(ExecutionError::Compile(stderr))
         }
     }
 }
@@ -0,0 +1,200 @@
+use nanoserde::DeJson;
+use std::{
+    path::Path,
+    process::Command,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+/// Limits for running code blocks. Resource limits are only applied on
+/// Linux, where a value of 0 means no limit.
+#[derive(Clone, Debug, DeJson)]
+#[nserde(default)]
+pub struct ExecutionLimits {
+    /// Maximum CPU time in seconds
+    pub cpu_time: u64,
+    /// Maximum data segment size in megabytes
+    pub memory: u64,
+    /// Maximum number of processes and threads for the user running the code
+    pub processes: u64,
+    /// Maximum number of bytes of output to keep
+    pub output_size: usize,
+    /// Only pass the variables in `environment` to the process
+    pub scrub_environment: bool,
+    pub environment: Vec<String>,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            cpu_time: 10,
+            memory: 0,
+            processes: 0,
+            output_size: 64 * 1024,
+            scrub_environment: true,
+            environment: [
+                "PATH",
+                "HOME",
+                "USER",
+                "LANG",
+                "LC_ALL",
+                "TERM",
+                "TMPDIR",
+                "CARGO_HOME",
+                "RUSTUP_HOME",
+                "RUSTUP_TOOLCHAIN",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl ExecutionLimits {
+    /// Runs the command in the directory, with a scrubbed environment.
+    pub fn sandbox(&self, command: &mut Command, dir: &Path) {
+        command.current_dir(dir);
+        if self.scrub_environment {
+            command.env_clear();
+            for name in self.environment.iter() {
+                if let Some(value) = std::env::var_os(name) {
+                    command.env(name, value);
+                }
+            }
+        }
+    }
+
+    /// Sets resource limits for the process before it starts.
+    #[cfg(target_os = "linux")]
+    pub fn limit_resources(&self, command: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        let limits = [
+            (libc::RLIMIT_CPU, self.cpu_time),
+            (libc::RLIMIT_DATA, self.memory * 1024 * 1024),
+            (libc::RLIMIT_NPROC, self.processes),
+        ];
+        // Only calls setrlimit, which is safe to use between fork and exec.
+        unsafe {
+            command.pre_exec(move || {
+                for (resource, value) in limits {
+                    if value == 0 {
+                        continue;
+                    }
+                    let limit = libc::rlimit {
+                        rlim_cur: value as libc::rlim_t,
+                        rlim_max: value as libc::rlim_t,
+                    };
+                    if libc::setrlimit(resource, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn limit_resources(&self, _command: &mut Command) {}
+
+    pub fn output_limit(&self) -> OutputLimit {
+        OutputLimit {
+            limit: self.output_size,
+            used: Arc::new(AtomicUsize::new(0)),
+            truncated: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// Keeps track of output size shared between stdout and stderr of a process.
+#[derive(Clone)]
+pub struct OutputLimit {
+    limit: usize,
+    used: Arc<AtomicUsize>,
+    truncated: Arc<AtomicBool>,
+}
+
+impl OutputLimit {
+    const TRUNCATED_MESSAGE: &'static str = "\n[output truncated]\n";
+
+    /// Returns the part of the text that fits within the limit, with a
+    /// message added the first time output is truncated.
+    pub fn take(&self, text: &str) -> Option<String> {
+        if self.limit == 0 {
+            return Some(text.to_string());
+        }
+        let used = self.used.fetch_add(text.len(), Ordering::Relaxed);
+        if used + text.len() <= self.limit {
+            return Some(text.to_string());
+        }
+        if self.truncated.swap(true, Ordering::Relaxed) {
+            return None;
+        }
+        let mut end = self.limit.saturating_sub(used).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        Some(format!("{}{}", &text[..end], Self::TRUNCATED_MESSAGE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(size: usize) -> OutputLimit {
+        ExecutionLimits {
+            output_size: size,
+            ..Default::default()
+        }
+        .output_limit()
+    }
+
+    #[test]
+    fn keeps_output_within_limit() {
+        let output_limit = limit(10);
+        assert_eq!(output_limit.take("hello"), Some("hello".to_string()));
+        assert_eq!(output_limit.take("world"), Some("world".to_string()));
+    }
+
+    #[test]
+    fn adds_message_once_when_truncating() {
+        let output_limit = limit(8);
+        assert_eq!(
+            output_limit.take("hello world"),
+            Some("hello wo\n[output truncated]\n".to_string())
+        );
+        assert_eq!(output_limit.take("more"), None);
+    }
+
+    #[test]
+    fn shares_limit_between_clones() {
+        let stdout = limit(6);
+        let stderr = stdout.clone();
+        assert_eq!(stdout.take("abcd"), Some("abcd".to_string()));
+        assert_eq!(
+            stderr.take("efgh"),
+            Some("ef\n[output truncated]\n".to_string())
+        );
+    }
+
+    #[test]
+    fn cuts_at_char_boundary() {
+        let output_limit = limit(4);
+        assert_eq!(
+            output_limit.take("abcé"),
+            Some("abc\n[output truncated]\n".to_string())
+        );
+    }
+
+    #[test]
+    fn no_limit_when_zero() {
+        let output_limit = limit(0);
+        let text = "x".repeat(100_000);
+        assert_eq!(output_limit.take(&text), Some(text.clone()));
+        assert_eq!(output_limit.take(&text), Some(text));
+    }
+}
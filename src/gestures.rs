@@ -0,0 +1,365 @@
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+
+/// Limits used to tell touch gestures apart, set in the keymap file.
+#[derive(Clone, Debug, DeJson)]
+#[nserde(default)]
+pub struct GestureSettings {
+    /// Shortest swipe, as a share of the screen width
+    pub swipe_distance: f32,
+    /// Longest distance a finger can move during a tap or long press, in pixels
+    pub tap_distance: f32,
+    /// Seconds to hold a finger still for a long press
+    pub long_press_time: f32,
+    /// Seconds to wait after a tap for a second finger, before it is a tap
+    pub tap_wait_time: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            swipe_distance: 0.1,
+            tap_distance: 20.,
+            long_press_time: 0.6,
+            tap_wait_time: 0.15,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gesture {
+    Tap(Vec2),
+    SwipeLeft,
+    SwipeRight,
+    TwoFingerTap,
+    LongPress,
+}
+
+struct TrackedTouch {
+    id: u64,
+    start: Vec2,
+    position: Vec2,
+    ended: bool,
+}
+
+impl TrackedTouch {
+    fn distance(&self) -> f32 {
+        self.start.distance(self.position)
+    }
+}
+
+/// Recognizes gestures from the touches of each frame. A gesture starts when
+/// the first finger touches the screen and ends when the last one is lifted,
+/// only a long press is recognized while the finger is still down. A tap is
+/// only given after a short wait, as a second finger touching down away from
+/// the first one right after it is lifted makes a two finger tap.
+pub struct Gestures {
+    settings: GestureSettings,
+    touches: Vec<TrackedTouch>,
+    started: f64,
+    finger_count: usize,
+    long_pressed: bool,
+    cancelled: bool,
+    /// Position of a finished tap and the time it ended
+    pending_tap: Option<(Vec2, f64)>,
+}
+
+impl Gestures {
+    pub fn new(settings: GestureSettings) -> Self {
+        Self {
+            settings,
+            touches: vec![],
+            started: 0.,
+            finger_count: 0,
+            long_pressed: false,
+            cancelled: false,
+            pending_tap: None,
+        }
+    }
+
+    /// Call once every frame, returns a gesture when one is recognized.
+    pub fn update(&mut self) -> Option<Gesture> {
+        self.recognize(&touches(), get_time(), screen_width())
+    }
+
+    /// Recognizes gestures from the touches of a frame, at a time in seconds.
+    fn recognize(&mut self, touches: &[Touch], time: f64, screen_width: f32) -> Option<Gesture> {
+        let mut pending = None;
+        for touch in touches {
+            pending = self.track(touch, time).or(pending);
+        }
+        if pending.is_some() {
+            return pending;
+        }
+        if self.touches.is_empty() {
+            return self.take_pending_tap(time);
+        }
+        if self.touches.iter().all(|touch| touch.ended) {
+            let gesture = self.finished_gesture(time, screen_width);
+            self.touches.clear();
+            if let Some(Gesture::Tap(position)) = gesture {
+                self.pending_tap = Some((position, time));
+                return None;
+            }
+            return gesture;
+        }
+        if self.is_long_press(time) {
+            self.long_pressed = true;
+            return Some(Gesture::LongPress);
+        }
+        None
+    }
+
+    /// Returns a pending tap when a new gesture starts close to it.
+    fn track(&mut self, touch: &Touch, time: f64) -> Option<Gesture> {
+        let mut pending = None;
+        match touch.phase {
+            TouchPhase::Started => {
+                if self.touches.is_empty() {
+                    match self.pending_tap.take() {
+                        Some((position, _))
+                            if position.distance(touch.position) > self.settings.tap_distance => {}
+                        tap => {
+                            pending = tap.map(|(position, _)| Gesture::Tap(position));
+                            self.started = time;
+                            self.finger_count = 0;
+                            self.long_pressed = false;
+                            self.cancelled = false;
+                        }
+                    }
+                }
+                self.touches.push(TrackedTouch {
+                    id: touch.id,
+                    start: touch.position,
+                    position: touch.position,
+                    ended: false,
+                });
+                self.finger_count += 1;
+            }
+            TouchPhase::Moved | TouchPhase::Stationary | TouchPhase::Ended => {
+                if let Some(tracked) = self.find(touch.id) {
+                    tracked.position = touch.position;
+                    tracked.ended = touch.phase == TouchPhase::Ended;
+                }
+            }
+            TouchPhase::Cancelled => {
+                self.cancelled = true;
+                if let Some(tracked) = self.find(touch.id) {
+                    tracked.ended = true;
+                }
+            }
+        }
+        pending
+    }
+
+    fn take_pending_tap(&mut self, time: f64) -> Option<Gesture> {
+        let (position, ended) = self.pending_tap?;
+        if time - ended < self.settings.tap_wait_time as f64 {
+            return None;
+        }
+        self.pending_tap = None;
+        Some(Gesture::Tap(position))
+    }
+
+    fn find(&mut self, id: u64) -> Option<&mut TrackedTouch> {
+        self.touches.iter_mut().find(|tracked| tracked.id == id)
+    }
+
+    fn elapsed(&self, time: f64) -> f32 {
+        (time - self.started) as f32
+    }
+
+    fn is_still(&self) -> bool {
+        self.touches
+            .iter()
+            .all(|touch| touch.distance() <= self.settings.tap_distance)
+    }
+
+    fn is_long_press(&self, time: f64) -> bool {
+        !self.long_pressed
+            && !self.cancelled
+            && self.finger_count == 1
+            && self.elapsed(time) >= self.settings.long_press_time
+            && self.is_still()
+    }
+
+    fn finished_gesture(&self, time: f64, screen_width: f32) -> Option<Gesture> {
+        if self.long_pressed || self.cancelled {
+            return None;
+        }
+        let quick = self.elapsed(time) < self.settings.long_press_time;
+        if self.finger_count >= 2 {
+            return (quick && self.is_still()).then_some(Gesture::TwoFingerTap);
+        }
+        let touch = self.touches.first()?;
+        let movement = touch.position - touch.start;
+        if movement.x.abs() >= self.settings.swipe_distance * screen_width
+            && movement.x.abs() > movement.y.abs()
+        {
+            if movement.x < 0. {
+                Some(Gesture::SwipeLeft)
+            } else {
+                Some(Gesture::SwipeRight)
+            }
+        } else if quick && self.is_still() {
+            Some(Gesture::Tap(touch.position))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN_WIDTH: f32 = 1000.;
+
+    fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> Touch {
+        Touch {
+            id,
+            phase,
+            position: vec2(x, y),
+        }
+    }
+
+    fn gestures() -> Gestures {
+        Gestures::new(GestureSettings::default())
+    }
+
+    #[test]
+    fn recognizes_swipes() {
+        let mut gestures = gestures();
+        let started = [touch(1, TouchPhase::Started, 500., 300.)];
+        assert_eq!(gestures.recognize(&started, 0., SCREEN_WIDTH), None);
+        let ended = [touch(1, TouchPhase::Ended, 300., 320.)];
+        assert_eq!(
+            gestures.recognize(&ended, 0.2, SCREEN_WIDTH),
+            Some(Gesture::SwipeLeft)
+        );
+        gestures.recognize(&started, 1., SCREEN_WIDTH);
+        let ended = [touch(1, TouchPhase::Ended, 700., 300.)];
+        assert_eq!(
+            gestures.recognize(&ended, 1.2, SCREEN_WIDTH),
+            Some(Gesture::SwipeRight)
+        );
+    }
+
+    #[test]
+    fn short_move_is_not_a_swipe() {
+        let mut gestures = gestures();
+        gestures.recognize(
+            &[touch(1, TouchPhase::Started, 500., 300.)],
+            0.,
+            SCREEN_WIDTH,
+        );
+        let ended = [touch(1, TouchPhase::Ended, 450., 300.)];
+        assert_eq!(gestures.recognize(&ended, 0.2, SCREEN_WIDTH), None);
+        assert_eq!(gestures.recognize(&[], 1., SCREEN_WIDTH), None);
+    }
+
+    #[test]
+    fn tap_waits_for_a_second_finger() {
+        let mut gestures = gestures();
+        gestures.recognize(
+            &[touch(1, TouchPhase::Started, 100., 100.)],
+            0.,
+            SCREEN_WIDTH,
+        );
+        let ended = [touch(1, TouchPhase::Ended, 105., 100.)];
+        assert_eq!(gestures.recognize(&ended, 0.1, SCREEN_WIDTH), None);
+        assert_eq!(gestures.recognize(&[], 0.2, SCREEN_WIDTH), None);
+        assert_eq!(
+            gestures.recognize(&[], 0.3, SCREEN_WIDTH),
+            Some(Gesture::Tap(vec2(105., 100.)))
+        );
+        assert_eq!(gestures.recognize(&[], 0.4, SCREEN_WIDTH), None);
+    }
+
+    #[test]
+    fn tap_close_to_a_tap_is_another_tap() {
+        let mut gestures = gestures();
+        gestures.recognize(
+            &[touch(1, TouchPhase::Started, 100., 100.)],
+            0.,
+            SCREEN_WIDTH,
+        );
+        gestures.recognize(
+            &[touch(1, TouchPhase::Ended, 100., 100.)],
+            0.05,
+            SCREEN_WIDTH,
+        );
+        let started = [touch(2, TouchPhase::Started, 102., 100.)];
+        assert_eq!(
+            gestures.recognize(&started, 0.1, SCREEN_WIDTH),
+            Some(Gesture::Tap(vec2(100., 100.)))
+        );
+        gestures.recognize(
+            &[touch(2, TouchPhase::Ended, 102., 100.)],
+            0.15,
+            SCREEN_WIDTH,
+        );
+        assert_eq!(
+            gestures.recognize(&[], 0.5, SCREEN_WIDTH),
+            Some(Gesture::Tap(vec2(102., 100.)))
+        );
+    }
+
+    #[test]
+    fn recognizes_long_press() {
+        let mut gestures = gestures();
+        let started = [touch(1, TouchPhase::Started, 100., 100.)];
+        assert_eq!(gestures.recognize(&started, 0., SCREEN_WIDTH), None);
+        let held = [touch(1, TouchPhase::Stationary, 100., 100.)];
+        assert_eq!(gestures.recognize(&held, 0.3, SCREEN_WIDTH), None);
+        assert_eq!(
+            gestures.recognize(&held, 0.7, SCREEN_WIDTH),
+            Some(Gesture::LongPress)
+        );
+        assert_eq!(gestures.recognize(&held, 0.8, SCREEN_WIDTH), None);
+        let ended = [touch(1, TouchPhase::Ended, 100., 100.)];
+        assert_eq!(gestures.recognize(&ended, 0.9, SCREEN_WIDTH), None);
+        assert_eq!(gestures.recognize(&[], 2., SCREEN_WIDTH), None);
+    }
+
+    #[test]
+    fn recognizes_two_finger_tap() {
+        let mut gestures = gestures();
+        let started = [
+            touch(1, TouchPhase::Started, 100., 100.),
+            touch(2, TouchPhase::Started, 200., 100.),
+        ];
+        assert_eq!(gestures.recognize(&started, 0., SCREEN_WIDTH), None);
+        let ended = [
+            touch(1, TouchPhase::Ended, 100., 100.),
+            touch(2, TouchPhase::Ended, 200., 100.),
+        ];
+        assert_eq!(
+            gestures.recognize(&ended, 0.1, SCREEN_WIDTH),
+            Some(Gesture::TwoFingerTap)
+        );
+    }
+
+    #[test]
+    fn second_finger_after_the_first_is_lifted_makes_a_two_finger_tap() {
+        let mut gestures = gestures();
+        gestures.recognize(
+            &[touch(1, TouchPhase::Started, 100., 100.)],
+            0.,
+            SCREEN_WIDTH,
+        );
+        gestures.recognize(
+            &[touch(1, TouchPhase::Ended, 100., 100.)],
+            0.05,
+            SCREEN_WIDTH,
+        );
+        let started = [touch(2, TouchPhase::Started, 200., 100.)];
+        assert_eq!(gestures.recognize(&started, 0.1, SCREEN_WIDTH), None);
+        let ended = [touch(2, TouchPhase::Ended, 200., 100.)];
+        assert_eq!(
+            gestures.recognize(&ended, 0.15, SCREEN_WIDTH),
+            Some(Gesture::TwoFingerTap)
+        );
+        assert_eq!(gestures.recognize(&[], 1., SCREEN_WIDTH), None);
+    }
+}
@@ -0,0 +1,442 @@
+use crate::prelude::GestureSettings;
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+use std::path::PathBuf;
+use {strum::IntoEnumIterator, strum_macros::EnumIter};
+
+/// Something the user can do with a key or mouse button in the slideshow.
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, DeJson, EnumIter)]
+pub enum Action {
+    quit,
+    previous,
+    next,
+    first,
+    last,
+    confirm,
+    toggle_shader,
+    screenshot,
+    copy_code,
+    focus_next_code_block,
+    run_code,
+    cancel_code,
+    clear_output,
+    scroll_output_up,
+    scroll_output_down,
+    focus_terminal,
+    overview,
+    search,
+    next_match,
+    previous_match,
+    talk_timer,
+    blackout,
+    whiteout,
+    help,
+}
+
+impl Action {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::quit => "Quit program",
+            Action::previous => "Go to previous slide",
+            Action::next => "Go to next slide",
+            Action::first => "Go to first slide",
+            Action::last => "Go to last slide",
+            Action::confirm => "Go to typed slide number, or slide selected in overview",
+            Action::toggle_shader => "Toggle shader",
+            Action::screenshot => "Save screenshot",
+            Action::copy_code => "Copy focused code block to clipboard",
+            Action::focus_next_code_block => "Focus next code block",
+            Action::run_code => "Execute code in focused code block, or type input to running code",
+            Action::cancel_code => "Cancel running code",
+            Action::clear_output => "Clear code output",
+            Action::scroll_output_up => "Scroll code output up",
+            Action::scroll_output_down => "Scroll code output down",
+            Action::focus_terminal => "Focus terminal, Ctrl-] to leave it",
+            Action::overview => "Show overview of all slides",
+            Action::search => "Search",
+            Action::next_match => "Go to next search match",
+            Action::previous_match => "Go to previous search match",
+            Action::talk_timer => "Toggle talk timer",
+            Action::blackout => "Fade to black, navigation keys show the slide again",
+            Action::whiteout => "Fade to white, navigation keys show the slide again",
+            Action::help => "Show this help screen",
+        }
+    }
+}
+
+/// A key with modifiers, a mouse button, or a typed character. Characters
+/// like `?` are matched on the text typed, so they work with any keyboard
+/// layout.
+#[derive(Clone, Debug)]
+pub enum Input {
+    Key {
+        key: KeyCode,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+    },
+    Mouse(MouseButton),
+    Character(char),
+}
+
+impl Input {
+    /// Parses names like `L`, `PageDown`, `Shift+N`, `MouseLeft` or `?`.
+    pub fn parse(name: &str) -> Option<Self> {
+        if let Some(button) = name.strip_prefix("Mouse") {
+            return match button {
+                "Left" => Some(Input::Mouse(MouseButton::Left)),
+                "Right" => Some(Input::Mouse(MouseButton::Right)),
+                "Middle" => Some(Input::Mouse(MouseButton::Middle)),
+                _ => None,
+            };
+        }
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if !c.is_ascii_alphanumeric() {
+                return Some(Input::Character(c));
+            }
+        }
+        let mut parts = name.split('+').collect::<Vec<&str>>();
+        let key = Self::key_code(parts.pop()?)?;
+        let (mut shift, mut ctrl, mut alt) = (false, false, false);
+        for modifier in parts {
+            match modifier {
+                "Shift" => shift = true,
+                "Ctrl" => ctrl = true,
+                "Alt" => alt = true,
+                _ => return None,
+            }
+        }
+        Some(Input::Key {
+            key,
+            shift,
+            ctrl,
+            alt,
+        })
+    }
+
+    fn is_pressed(&self, typed: Option<char>) -> bool {
+        match self {
+            Input::Key {
+                key,
+                shift,
+                ctrl,
+                alt,
+            } => {
+                is_key_pressed(*key)
+                    && *shift
+                        == (is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift))
+                    && *ctrl
+                        == (is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl))
+                    && *alt == (is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt))
+            }
+            Input::Mouse(button) => is_mouse_button_pressed(*button),
+            Input::Character(c) => typed == Some(*c),
+        }
+    }
+
+    fn key_code(name: &str) -> Option<KeyCode> {
+        let key = match name {
+            "A" => KeyCode::A,
+            "B" => KeyCode::B,
+            "C" => KeyCode::C,
+            "D" => KeyCode::D,
+            "E" => KeyCode::E,
+            "F" => KeyCode::F,
+            "G" => KeyCode::G,
+            "H" => KeyCode::H,
+            "I" => KeyCode::I,
+            "J" => KeyCode::J,
+            "K" => KeyCode::K,
+            "L" => KeyCode::L,
+            "M" => KeyCode::M,
+            "N" => KeyCode::N,
+            "O" => KeyCode::O,
+            "P" => KeyCode::P,
+            "Q" => KeyCode::Q,
+            "R" => KeyCode::R,
+            "S" => KeyCode::S,
+            "T" => KeyCode::T,
+            "U" => KeyCode::U,
+            "V" => KeyCode::V,
+            "W" => KeyCode::W,
+            "X" => KeyCode::X,
+            "Y" => KeyCode::Y,
+            "Z" => KeyCode::Z,
+            "F1" => KeyCode::F1,
+            "F2" => KeyCode::F2,
+            "F3" => KeyCode::F3,
+            "F4" => KeyCode::F4,
+            "F5" => KeyCode::F5,
+            "F6" => KeyCode::F6,
+            "F7" => KeyCode::F7,
+            "F8" => KeyCode::F8,
+            "F9" => KeyCode::F9,
+            "F10" => KeyCode::F10,
+            "F11" => KeyCode::F11,
+            "F12" => KeyCode::F12,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "Space" => KeyCode::Space,
+            "Enter" => KeyCode::Enter,
+            "Escape" => KeyCode::Escape,
+            "Tab" => KeyCode::Tab,
+            "Backspace" => KeyCode::Backspace,
+            "Delete" => KeyCode::Delete,
+            "Insert" => KeyCode::Insert,
+            "Period" => KeyCode::Period,
+            "Comma" => KeyCode::Comma,
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+#[derive(Clone, Debug, DeJson)]
+pub struct KeyBinding {
+    pub action: Action,
+    pub keys: Vec<String>,
+}
+
+impl KeyBinding {
+    fn new(action: Action, keys: &[&str]) -> Self {
+        Self {
+            action,
+            keys: keys.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(DeJson)]
+struct KeymapFile {
+    #[nserde(default)]
+    bindings: Vec<KeyBinding>,
+    #[nserde(default)]
+    gestures: GestureSettings,
+}
+
+/// Maps keys and mouse buttons to actions. Bindings in the keymap file
+/// replace the default bindings of the same action.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<KeyBinding>,
+    inputs: Vec<(Action, Input)>,
+    gesture_settings: GestureSettings,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(Self::default_bindings(), GestureSettings::default())
+    }
+}
+
+impl Keymap {
+    const FIXED_HELP: [&'static str; 3] = [
+        "0-9 - Type a slide number, Backspace removes a digit",
+        "Alt+1-9 - Focus that code block",
+        "Escape - Close overview, cancel search or slide number, or stop typing input to code",
+    ];
+
+    /// Exits if a key name isn't known.
+    pub fn new(bindings: Vec<KeyBinding>, gesture_settings: GestureSettings) -> Self {
+        let mut inputs = vec![];
+        for binding in bindings.iter() {
+            for key in binding.keys.iter() {
+                match Input::parse(key) {
+                    Some(input) => inputs.push((binding.action, input)),
+                    None => {
+                        eprintln!("Unknown key in keymap: {}", key);
+                        std::process::exit(2);
+                    }
+                }
+            }
+        }
+        Self {
+            bindings,
+            inputs,
+            gesture_settings,
+        }
+    }
+
+    pub async fn load(keymap_path: PathBuf) -> Self {
+        let path = keymap_path.as_path().to_str().unwrap().to_owned();
+        debug!("Keymap path: {}", path);
+        let (mut bindings, gesture_settings) = match load_string(&path).await {
+            Ok(json) => match KeymapFile::deserialize_json(&json) {
+                Ok(keymap_file) => (keymap_file.bindings, keymap_file.gestures),
+                Err(_) => {
+                    eprintln!("Couldn't parse keymap file: {}", path);
+                    std::process::exit(2);
+                }
+            },
+            Err(_) => (vec![], GestureSettings::default()),
+        };
+        for binding in Self::default_bindings() {
+            if !bindings
+                .iter()
+                .any(|configured| configured.action == binding.action)
+            {
+                bindings.push(binding);
+            }
+        }
+        Self::new(bindings, gesture_settings)
+    }
+
+    pub fn gesture_settings(&self) -> GestureSettings {
+        self.gesture_settings.clone()
+    }
+
+    fn default_bindings() -> Vec<KeyBinding> {
+        vec![
+            KeyBinding::new(Action::quit, &["Q", "Escape"]),
+            KeyBinding::new(Action::previous, &["Left", "H", "MouseRight"]),
+            KeyBinding::new(Action::next, &["Right", "L", "MouseLeft"]),
+            KeyBinding::new(Action::first, &["Up", "K", "Home"]),
+            KeyBinding::new(Action::last, &["Down", "J", "End"]),
+            KeyBinding::new(Action::confirm, &["Enter", "G"]),
+            KeyBinding::new(Action::toggle_shader, &["Space"]),
+            KeyBinding::new(Action::screenshot, &["S"]),
+            KeyBinding::new(Action::copy_code, &["C"]),
+            KeyBinding::new(Action::focus_next_code_block, &["Tab"]),
+            KeyBinding::new(Action::run_code, &["Enter"]),
+            KeyBinding::new(Action::cancel_code, &["X"]),
+            KeyBinding::new(Action::clear_output, &["Backspace"]),
+            KeyBinding::new(Action::scroll_output_up, &["Shift+Up"]),
+            KeyBinding::new(Action::scroll_output_down, &["Shift+Down"]),
+            KeyBinding::new(Action::focus_terminal, &["T"]),
+            KeyBinding::new(Action::overview, &["O"]),
+            KeyBinding::new(Action::search, &["/"]),
+            KeyBinding::new(Action::next_match, &["N"]),
+            KeyBinding::new(Action::previous_match, &["Shift+N"]),
+            KeyBinding::new(Action::talk_timer, &["I"]),
+            KeyBinding::new(Action::blackout, &["B"]),
+            KeyBinding::new(Action::whiteout, &["W"]),
+            KeyBinding::new(Action::help, &["?"]),
+        ]
+    }
+
+    /// Actions that were triggered this frame. Characters are matched against
+    /// the character typed this frame, if any.
+    pub fn pressed_actions(&self, typed: Option<char>) -> Vec<Action> {
+        let mut actions = vec![];
+        for (action, input) in self.inputs.iter() {
+            if !actions.contains(action) && input.is_pressed(typed) {
+                actions.push(*action);
+            }
+        }
+        actions
+    }
+
+    /// Help text listing all actions with their keys, in the order of the
+    /// actions.
+    pub fn help_text(&self) -> String {
+        let mut lines = vec!["Shortcuts".to_string(), "".to_string()];
+        for action in Action::iter() {
+            let keys = self
+                .bindings
+                .iter()
+                .filter(|binding| binding.action == action)
+                .flat_map(|binding| binding.keys.iter().map(String::as_str))
+                .collect::<Vec<&str>>();
+            if !keys.is_empty() {
+                lines.push(format!("{} - {}", keys.join(" / "), action.description()));
+            }
+        }
+        lines.extend(Self::FIXED_HELP.iter().map(|line| line.to_string()));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        assert!(matches!(
+            Input::parse("Shift+N"),
+            Some(Input::Key {
+                key: KeyCode::N,
+                shift: true,
+                ctrl: false,
+                alt: false,
+            })
+        ));
+        assert!(matches!(
+            Input::parse("Ctrl+Alt+PageDown"),
+            Some(Input::Key {
+                key: KeyCode::PageDown,
+                shift: false,
+                ctrl: true,
+                alt: true,
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_letters_as_keys_and_symbols_as_characters() {
+        assert!(matches!(
+            Input::parse("L"),
+            Some(Input::Key {
+                key: KeyCode::L,
+                ..
+            })
+        ));
+        assert!(matches!(Input::parse("?"), Some(Input::Character('?'))));
+        assert!(matches!(Input::parse("/"), Some(Input::Character('/'))));
+    }
+
+    #[test]
+    fn parses_mouse_buttons() {
+        assert!(matches!(
+            Input::parse("MouseRight"),
+            Some(Input::Mouse(MouseButton::Right))
+        ));
+        assert!(Input::parse("MouseBack").is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_modifiers() {
+        assert!(Input::parse("Hyper+L").is_none());
+        assert!(Input::parse("Foo").is_none());
+        assert!(Input::parse("Shift+").is_none());
+    }
+
+    #[test]
+    fn reads_keymap_file() {
+        let keymap_file = KeymapFile::deserialize_json(
+            r#"{"bindings": [{"action": "next", "keys": ["PageDown", "Space"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(keymap_file.bindings.len(), 1);
+        assert_eq!(keymap_file.bindings[0].action, Action::next);
+        assert_eq!(keymap_file.bindings[0].keys, vec!["PageDown", "Space"]);
+    }
+
+    #[test]
+    fn help_text_lists_keys_in_action_order() {
+        let help_text = Keymap::default().help_text();
+        let quit = help_text.find("Q / Escape - Quit program").unwrap();
+        let help = help_text.find("? - Show this help screen").unwrap();
+        assert!(quit < help);
+        assert!(help_text.contains("Shift+N - Go to previous search match"));
+    }
+
+    #[test]
+    fn help_text_only_lists_bound_actions() {
+        let keymap = Keymap::new(
+            vec![KeyBinding::new(Action::next, &["F5"])],
+            GestureSettings::default(),
+        );
+        let help_text = keymap.help_text();
+        assert!(help_text.contains("F5 - Go to next slide"));
+        assert!(!help_text.contains("Quit program"));
+    }
+}
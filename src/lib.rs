@@ -1,16 +1,40 @@
+pub mod ansi;
 pub mod app_options;
+pub mod blank_screen;
+pub mod cargo_project;
+pub mod chrome;
 pub mod clipboard;
+pub mod code_block;
 pub mod code_box_builder;
+pub mod code_checker;
 pub mod codebox;
+pub mod directive;
 pub mod drawbox;
 pub mod executable_code;
+pub mod execution_limits;
+pub mod gestures;
 pub mod hex_color;
 pub mod imagebox;
+pub mod keymap;
 pub mod markdowntoslides;
+pub mod overview;
 pub mod prelude;
+pub mod presenter_console;
+pub mod presenter_view;
+pub mod prompt_box;
+pub mod recorded_outputs;
+pub mod runners;
+pub mod running_code;
+pub mod search;
 pub mod shaders;
 pub mod show_help;
+pub mod slide_number_prompt;
+pub mod slide_sync;
 pub mod slider;
+pub mod talk_timer;
+pub mod terminal;
+pub mod terminal_box;
+pub mod terminal_screen;
 pub mod textbox;
 pub mod theme;
 pub mod transition;
@@ -6,6 +6,19 @@ use {clap::Parser, macroquad::prelude::*, quad_url::get_program_parameters};
 
 use rusty_slider::prelude::*;
 
+/// With `Alt`, the digit keys focus that code block on the slide.
+const CODE_BLOCK_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
 fn window_conf() -> Conf {
     Conf {
         window_title: "Rusty Slider".to_owned(),
@@ -14,10 +27,20 @@ fn window_conf() -> Conf {
     }
 }
 
-#[macroquad::main(window_conf())]
-async fn main() {
+fn main() {
     let options = AppOptions::parse_from(get_program_parameters().iter());
+    #[cfg(not(target_arch = "wasm32"))]
+    if options.check_code {
+        std::process::exit(CodeChecker::load(&options).run(&options));
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if options.record_code {
+        std::process::exit(CodeChecker::load(&options).record(&options));
+    }
+    macroquad::Window::from_config(window_conf(), run(options));
+}
 
+async fn run(options: AppOptions) {
     let theme = Theme::load(options.theme_path()).await;
     debug!(
         "background_color: {:?} text_color: {:?} heading_color{:?}",
@@ -25,7 +48,17 @@ async fn main() {
     );
     let mut shader_activated = theme.shader;
     let mut slides = Slides::load(options.clone(), theme).await;
-    let mut show_help = ShowHelp::new();
+    let keymap = Keymap::load(options.keymap_path()).await;
+    let mut show_help = ShowHelp::new(keymap.help_text());
+    let mut gestures = Gestures::new(keymap.gesture_settings());
+    // Touches are handled as gestures, so a swipe doesn't also click
+    simulate_mouse_with_touch(false);
+    let mut slide_number_prompt = SlideNumberPrompt::new();
+    let presenter_view = options.presenter.then(PresenterView::new);
+    let mut slide_sync =
+        (options.sync || options.presenter).then(|| SlideSync::new(options.sync_path()));
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut presenter_console = options.presenter_console.then(PresenterConsole::start);
     let shader_material = load_material(
         ShaderSource::Glsl {
             vertex: crt::VERTEX,
@@ -35,79 +68,269 @@ async fn main() {
     )
     .unwrap();
 
-    loop {
-        #[cfg(not(target_arch = "wasm32"))]
-        if is_key_pressed(KeyCode::Q) | is_key_pressed(KeyCode::Escape) {
-            break;
-        }
-        if is_key_pressed(KeyCode::Left)
-            || is_key_pressed(KeyCode::H)
-            || is_mouse_button_pressed(MouseButton::Right)
-        {
-            slides.prev();
-        }
-        if is_key_pressed(KeyCode::Right)
-            || is_key_pressed(KeyCode::L)
-            || is_mouse_button_pressed(MouseButton::Left)
-        {
-            slides.next();
-        }
-        if is_key_pressed(KeyCode::Up)
-            || is_key_pressed(KeyCode::K)
-            || is_key_pressed(KeyCode::Home)
-        {
-            slides.first();
-        }
-        if is_key_pressed(KeyCode::Down)
-            || is_key_pressed(KeyCode::J)
-            || is_key_pressed(KeyCode::End)
-        {
-            slides.last();
-        }
-        if is_key_pressed(KeyCode::Space) {
-            shader_activated = !shader_activated;
-        }
-        if is_key_pressed(KeyCode::C) {
-            slides.copy_codeblock();
-        }
-        match get_char_pressed() {
-            Some('?') => show_help.toggle_show(),
-            _ => (),
+    'main: loop {
+        let terminal_focused = slides.is_terminal_focused();
+        let reading_code_input = slides.is_reading_code_input();
+        let mut take_screenshot = false;
+        let gesture = gestures.update();
+        if terminal_focused {
+            handle_terminal_input(&mut slides);
+        } else if reading_code_input {
+            handle_code_input(&mut slides);
+        } else if slides.is_typing_search() {
+            handle_search_input(&mut slides);
+        } else if slides.is_showing_overview() {
+            handle_overview_input(&mut slides, &keymap, gesture);
+        } else {
+            match gesture {
+                Some(Gesture::Tap(_)) | Some(Gesture::SwipeLeft) => slides.next(),
+                Some(Gesture::SwipeRight) => slides.prev(),
+                Some(Gesture::TwoFingerTap) => slides.open_overview(),
+                Some(Gesture::LongPress) => show_help.toggle_show(),
+                None => (),
+            }
+            let typed = get_char_pressed();
+            if is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt) {
+                for (index, key) in CODE_BLOCK_KEYS.iter().enumerate() {
+                    if is_key_pressed(*key) {
+                        slides.focus_code_block(index);
+                    }
+                }
+            } else if let Some(c @ '0'..='9') = typed {
+                slide_number_prompt.push_digit(c);
+            }
+            if slide_number_prompt.is_active() {
+                if keymap.pressed_actions(typed).contains(&Action::confirm) {
+                    if let Some(slide_number) = slide_number_prompt.take() {
+                        slides.goto(slide_number);
+                    }
+                }
+                if is_key_pressed(KeyCode::Backspace) {
+                    slide_number_prompt.remove_digit();
+                }
+                if is_key_pressed(KeyCode::Escape) {
+                    slide_number_prompt.clear();
+                }
+            } else if slides.is_search_active() && is_key_pressed(KeyCode::Escape) {
+                slides.clear_search();
+            } else {
+                for action in keymap.pressed_actions(typed) {
+                    match action {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        Action::quit => break 'main,
+                        #[cfg(target_arch = "wasm32")]
+                        Action::quit => (),
+                        Action::previous => slides.prev(),
+                        Action::next => slides.next(),
+                        Action::first => slides.first(),
+                        Action::last => slides.last(),
+                        Action::confirm => (),
+                        Action::toggle_shader => shader_activated = !shader_activated,
+                        Action::screenshot => take_screenshot = true,
+                        Action::copy_code => slides.copy_codeblock(),
+                        Action::focus_next_code_block => slides.focus_next_code_block(),
+                        Action::run_code => {
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if options.enable_code_execution {
+                                slides.run_code_block();
+                            } else {
+                                slides.replay_code_block();
+                            }
+                            #[cfg(target_arch = "wasm32")]
+                            slides.replay_code_block();
+                        }
+                        Action::cancel_code => slides.cancel_code_block(),
+                        Action::clear_output => slides.clear_code_output(),
+                        Action::scroll_output_up => slides.scroll_code_output(1),
+                        Action::scroll_output_down => slides.scroll_code_output(-1),
+                        Action::focus_terminal => slides.focus_terminal(),
+                        Action::overview => slides.open_overview(),
+                        Action::search => slides.start_search(),
+                        Action::next_match if slides.is_search_active() => {
+                            slides.next_search_match()
+                        }
+                        Action::previous_match if slides.is_search_active() => {
+                            slides.previous_search_match()
+                        }
+                        Action::next_match | Action::previous_match => (),
+                        Action::talk_timer => slides.toggle_talk_timer(),
+                        Action::blackout => slides.toggle_blank_screen(BLACK),
+                        Action::whiteout => slides.toggle_blank_screen(WHITE),
+                        Action::help => show_help.toggle_show(),
+                    }
+                }
+            }
         }
+
         #[cfg(not(target_arch = "wasm32"))]
-        if options.enable_code_execution && is_key_pressed(KeyCode::Enter) {
-            slides.run_code_block();
+        if let Some(presenter_console) = &mut presenter_console {
+            if presenter_console.update(&mut slides) {
+                break 'main;
+            }
+        }
+        if let Some(slide_sync) = &mut slide_sync {
+            slide_sync.update(&mut slides);
         }
-
         slides.update(get_frame_time());
         slides.draw();
         let texture = slides.texture();
 
         set_default_camera();
         clear_background(BLACK);
-        if shader_activated {
-            gl_use_material(&shader_material);
-        }
-        draw_texture_ex(
-            &texture,
-            0.,
-            0.,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(screen_width(), screen_height())),
-                flip_y: true,
-                ..Default::default()
-            },
-        );
-        if shader_activated {
-            gl_use_default_material();
+        if let Some(presenter_view) = &presenter_view {
+            let next_texture = slides.next_slide_texture();
+            presenter_view.draw(
+                &texture,
+                next_texture.as_ref(),
+                slides.notes(),
+                slides.active_slide_number(),
+                slides.slide_count(),
+                slides.talk_timer(),
+            );
+        } else {
+            if shader_activated {
+                gl_use_material(&shader_material);
+            }
+            draw_texture_ex(
+                &texture,
+                0.,
+                0.,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(screen_width(), screen_height())),
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+            if shader_activated {
+                gl_use_default_material();
+            }
+            slides.draw_blank_screen();
         }
         show_help.draw();
+        slide_number_prompt.draw();
+        slides.draw_search();
 
-        if is_key_pressed(KeyCode::S) {
+        if take_screenshot {
             get_screen_data().export_png(&options.screenshot.to_string_lossy());
         }
+        // Drawn after the screenshot is taken, to keep it off screenshots
+        slides.draw_talk_timer();
 
         next_frame().await
     }
 }
+
+/// Typed text goes to the running code, `Ctrl-D` closes its input and
+/// `Escape` goes back to using shortcuts.
+fn handle_code_input(slides: &mut Slides) {
+    let control = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+    if control && is_key_pressed(KeyCode::D) {
+        slides.close_code_input();
+    }
+    while let Some(c) = get_char_pressed() {
+        if !control && !c.is_control() {
+            slides.add_code_input(c);
+        }
+    }
+    if is_key_pressed(KeyCode::Backspace) {
+        slides.remove_code_input();
+    }
+    if is_key_pressed(KeyCode::Enter) {
+        slides.send_code_input();
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        slides.stop_code_input();
+    }
+}
+
+/// Sends key presses to the focused terminal, `Ctrl-]` leaves the terminal.
+fn handle_terminal_input(slides: &mut Slides) {
+    let control = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+    if control && is_key_pressed(KeyCode::RightBracket) {
+        slides.unfocus_terminal();
+        return;
+    }
+    while let Some(c) = get_char_pressed() {
+        if control && c.is_ascii_alphabetic() {
+            slides.send_to_terminal(&[c.to_ascii_uppercase() as u8 & 0x1f]);
+        } else if control && ('\u{1}'..='\u{1a}').contains(&c) {
+            slides.send_to_terminal(&[c as u8]);
+        } else if !c.is_control() {
+            slides.send_to_terminal(c.to_string().as_bytes());
+        }
+    }
+    let keys: [(KeyCode, &[u8]); 12] = [
+        (KeyCode::Enter, b"\r"),
+        (KeyCode::KpEnter, b"\r"),
+        (KeyCode::Backspace, b"\x7f"),
+        (KeyCode::Tab, b"\t"),
+        (KeyCode::Escape, b"\x1b"),
+        (KeyCode::Up, b"\x1b[A"),
+        (KeyCode::Down, b"\x1b[B"),
+        (KeyCode::Right, b"\x1b[C"),
+        (KeyCode::Left, b"\x1b[D"),
+        (KeyCode::Home, b"\x1b[H"),
+        (KeyCode::End, b"\x1b[F"),
+        (KeyCode::Delete, b"\x1b[3~"),
+    ];
+    for (key, bytes) in keys {
+        if is_key_pressed(key) {
+            slides.send_to_terminal(bytes);
+        }
+    }
+}
+
+/// The previous and next keys move the selection in the overview left and
+/// right, the first and last keys move it up and down. The confirm key, a
+/// click or a tap goes to the selected slide, and the overview key, `Escape`
+/// or a two finger tap closes the overview.
+fn handle_overview_input(slides: &mut Slides, keymap: &Keymap, gesture: Option<Gesture>) {
+    let actions = keymap.pressed_actions(get_char_pressed());
+    if actions.contains(&Action::overview)
+        || is_key_pressed(KeyCode::Escape)
+        || gesture == Some(Gesture::TwoFingerTap)
+    {
+        slides.close_overview();
+        return;
+    }
+    let clicked = match gesture {
+        Some(Gesture::Tap(position)) => Some(position.into()),
+        _ => is_mouse_button_pressed(MouseButton::Left).then(mouse_position),
+    };
+    if let Some(position) = clicked {
+        if slides.select_overview_slide_at(position) {
+            slides.goto_overview_selection();
+        }
+        return;
+    }
+    for action in actions {
+        match action {
+            Action::previous => slides.move_overview_selection(-1, 0),
+            Action::next => slides.move_overview_selection(1, 0),
+            Action::first => slides.move_overview_selection(0, -1),
+            Action::last => slides.move_overview_selection(0, 1),
+            Action::confirm => slides.goto_overview_selection(),
+            _ => (),
+        }
+    }
+}
+
+/// Typed text is added to the search query, `Enter` searches and `Escape`
+/// cancels the search.
+fn handle_search_input(slides: &mut Slides) {
+    while let Some(c) = get_char_pressed() {
+        if !c.is_control() {
+            slides.add_search_input(c);
+        }
+    }
+    if is_key_pressed(KeyCode::Backspace) {
+        slides.remove_search_input();
+    }
+    if is_key_pressed(KeyCode::Enter) {
+        slides.finish_search();
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        slides.clear_search();
+    }
+}
@@ -10,6 +10,7 @@ pub struct MarkdownToSlides {
     font_italic: Font,
     font_code: Font,
     code_box_builder: CodeBoxBuilder,
+    runners: Runners,
 }
 
 impl MarkdownToSlides {
@@ -19,16 +20,13 @@ impl MarkdownToSlides {
         font_bold: Font,
         font_italic: Font,
         font_code: Font,
+        code_box_builder: CodeBoxBuilder,
+        runners: Runners,
     ) -> Self {
-        let code_box_builder = CodeBoxBuilder::new(
-            theme.clone(),
-            font_code.clone(),
-            font_bold.clone(),
-            font_italic.clone(),
-        );
         Self {
             theme,
             code_box_builder,
+            runners,
             font_text,
             font_bold,
             font_italic,
@@ -36,13 +34,16 @@ impl MarkdownToSlides {
         }
     }
 
-    pub fn parse(&self, markdown: String) -> Vec<Slide> {
-        let tokens = markdown::tokenize(&markdown);
-        let slide_blocks = self.split_tokens_into_slides(tokens);
-        self.build_slides(slide_blocks)
+    /// The directives are the ones taken out of the markdown.
+    pub fn parse(&self, markdown: String, directives: &[Directive]) -> Vec<Slide> {
+        let slide_blocks = Self::split_slides(&markdown);
+        self.build_slides(slide_blocks, directives)
     }
 
-    fn split_tokens_into_slides(&self, tokens: Vec<Block>) -> Vec<Vec<Block>> {
+    /// The blocks of each slide, which are separated by horizontal rules.
+    /// Also used to find the code blocks of each slide without a window.
+    pub fn split_slides(markdown: &str) -> Vec<Vec<Block>> {
+        let tokens = markdown::tokenize(markdown);
         let mut slides: Vec<Vec<Block>> = vec![];
         let mut blocks: Vec<Block> = vec![];
         for block in tokens.iter() {
@@ -61,35 +62,70 @@ impl MarkdownToSlides {
         slides
     }
 
-    fn build_slides(&self, slide_blocks: Vec<Vec<Block>>) -> Vec<Slide> {
+    /// Until the terminal is started, it shows the command that will run.
+    fn build_terminal_box(&self, command: &str) -> TerminalBox {
+        let command = command.trim();
+        let mut screen = TerminalScreen::new(self.theme.terminal_rows, self.theme.terminal_columns);
+        screen.write(format!("$ {}", command).as_bytes());
+        TerminalBox::new(
+            command.to_string(),
+            self.code_box_builder.build_screen_box(&screen, false),
+        )
+    }
+
+    fn build_slides(&self, slide_blocks: Vec<Vec<Block>>, directives: &[Directive]) -> Vec<Slide> {
         let mut slides = vec![];
         for blocks in slide_blocks.iter() {
-            slides.push(self.build_slide(blocks));
+            slides.push(self.build_slide(blocks, directives));
         }
         slides
     }
 
-    fn build_slide(&self, blocks: &[Block]) -> Slide {
-        let (draw_boxes, background_path) =
-            self.blocks_to_draw_boxes(blocks, None, TextBoxStyle::Standard);
-        Slide::new(
-            draw_boxes,
-            self.find_first_code_block(blocks),
-            self.theme.align,
-            self.theme.horizontal_offset,
-            background_path,
-        )
-    }
-
-    fn find_first_code_block(&self, blocks: &[Block]) -> Option<ExecutableCode> {
+    fn build_slide(&self, blocks: &[Block], directives: &[Directive]) -> Slide {
+        let mut notes = vec![];
+        let mut hidden_chrome = HiddenChrome::default();
+        let mut time_budget = None;
+        let mut visible_blocks = vec![];
         for block in blocks.iter() {
-            if let Block::CodeBlock(Some(language), code) = block {
-                if let Some(cb) = ExecutableCode::from(language, code) {
-                    return Some(cb);
+            match block {
+                Block::CodeBlock(Some(language), index) if language == Directive::LANGUAGE => {
+                    match index
+                        .trim()
+                        .parse()
+                        .ok()
+                        .and_then(|index: usize| directives.get(index))
+                    {
+                        Some(Directive::Notes(text)) => notes.push(text.to_owned()),
+                        Some(Directive::HideChrome(hidden)) => hidden_chrome = *hidden,
+                        Some(Directive::TimeBudget(budget)) => time_budget = *budget,
+                        None => (),
+                    }
                 }
+                _ => visible_blocks.push(block.to_owned()),
             }
         }
-        None
+        let title = visible_blocks
+            .iter()
+            .find_map(|block| match block {
+                Block::Header(spans, _) => Some(Self::spans_to_text(spans)),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let (draw_boxes, code_blocks, background_path) =
+            self.blocks_to_draw_boxes(&visible_blocks, None, TextBoxStyle::Standard);
+        let mut slide = Slide::new(
+            draw_boxes,
+            code_blocks,
+            self.theme.code_focus_color,
+            self.theme.align,
+            self.theme.horizontal_offset,
+            background_path,
+        );
+        slide.title = title;
+        slide.notes = notes.join("\n\n");
+        slide.hidden_chrome = hidden_chrome;
+        slide.time_budget = time_budget;
+        slide
     }
 
     fn blocks_to_draw_boxes(
@@ -97,8 +133,9 @@ impl MarkdownToSlides {
         blocks: &[Block],
         background_color: Option<Color>,
         style: TextBoxStyle,
-    ) -> (Vec<DrawBox>, Option<String>) {
+    ) -> (Vec<DrawBox>, Vec<CodeBlock>, Option<String>) {
         let mut draw_boxes = vec![];
+        let mut code_blocks = vec![];
         let mut text_lines = vec![];
         let mut background_path: Option<String> = None;
         for block in blocks.iter() {
@@ -184,7 +221,7 @@ impl MarkdownToSlides {
                         )));
                         text_lines = Vec::new();
                     }
-                    let (inner_blocks, image_path) = self.blocks_to_draw_boxes(
+                    let (inner_blocks, inner_code_blocks, image_path) = self.blocks_to_draw_boxes(
                         blocks,
                         Some(self.theme.blockquote_background_color),
                         TextBoxStyle::Blockquote {
@@ -196,8 +233,25 @@ impl MarkdownToSlides {
                     if image_path.is_some() {
                         background_path = image_path;
                     }
+                    let offset = draw_boxes.len();
+                    code_blocks.extend(inner_code_blocks.into_iter().map(|mut code_block| {
+                        code_block.draw_box_index += offset;
+                        code_block
+                    }));
                     draw_boxes.extend(inner_blocks);
                 }
+                Block::CodeBlock(Some(language), command) if language == "terminal" => {
+                    if !text_lines.is_empty() {
+                        draw_boxes.push(DrawBox::Text(TextBox::new(
+                            text_lines,
+                            self.theme.vertical_offset,
+                            background_color,
+                            style.clone(),
+                        )));
+                        text_lines = Vec::new();
+                    }
+                    draw_boxes.push(DrawBox::Terminal(self.build_terminal_box(command)));
+                }
                 Block::CodeBlock(language, code) => {
                     if !text_lines.is_empty() {
                         draw_boxes.push(DrawBox::Text(TextBox::new(
@@ -208,9 +262,19 @@ impl MarkdownToSlides {
                         )));
                         text_lines = Vec::new();
                     }
+                    let executable_code = language
+                        .as_ref()
+                        .map(|language| ExecutableCode::from(language, code, &self.runners));
+                    let visible_code = match &executable_code {
+                        Some(executable_code) => executable_code.code(),
+                        None => code.to_owned(),
+                    };
+                    if let Some(executable_code) = executable_code {
+                        code_blocks.push(CodeBlock::new(draw_boxes.len(), executable_code));
+                    }
                     draw_boxes.push(DrawBox::Code(
                         self.code_box_builder
-                            .build_draw_box(language.to_owned(), code.to_owned()),
+                            .build_draw_box(language.to_owned(), visible_code),
                     ));
                 }
 
@@ -225,7 +289,7 @@ impl MarkdownToSlides {
                 style,
             )));
         }
-        (draw_boxes, background_path)
+        (draw_boxes, code_blocks, background_path)
     }
 
     fn is_image(&self, spans: &[Span]) -> bool {
@@ -279,6 +343,17 @@ impl MarkdownToSlides {
         partials
     }
 
+    fn spans_to_text(spans: &[Span]) -> String {
+        spans
+            .iter()
+            .map(|span| match span {
+                Span::Text(text) | Span::Code(text) => text.to_owned(),
+                Span::Emphasis(spans) | Span::Strong(spans) => Self::spans_to_text(spans),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
     fn build_list_box(&self, items: &[ListItem], bullet: Option<&String>) -> Vec<TextLine> {
         let mut lines: Vec<TextLine> = vec![];
         for (index, item) in items.iter().enumerate() {
@@ -0,0 +1,172 @@
+use crate::prelude::*;
+use macroquad::prelude::*;
+
+/// Shows all slides as a grid of thumbnails to pick a slide from. The
+/// thumbnails are rendered once and kept until the screen size or the
+/// content of their slide changes.
+pub struct Overview {
+    thumbnails: Vec<Option<RenderTarget>>,
+    screen_size: (Width, Height),
+    selected: usize,
+    first_row: usize,
+    pub showing: bool,
+}
+
+impl Overview {
+    const COLUMNS: usize = 4;
+    const SPACING: f32 = 40.;
+    const SELECTED_THICKNESS: f32 = 6.;
+    const NUMBER_FONT_SIZE: f32 = 32.;
+    const NUMBER_COLOR: Color = Color::new(0.5, 0.5, 0.5, 1.);
+
+    pub fn new(slide_count: usize) -> Self {
+        Self {
+            thumbnails: vec![None; slide_count],
+            screen_size: (0., 0.),
+            selected: 0,
+            first_row: 0,
+            showing: false,
+        }
+    }
+
+    pub fn open(&mut self, selected: usize) {
+        self.showing = true;
+        self.select(selected);
+    }
+
+    pub fn close(&mut self) {
+        self.showing = false;
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn has_thumbnail(&self, index: usize) -> bool {
+        matches!(self.thumbnails.get(index), Some(Some(_)))
+    }
+
+    /// The thumbnail is rendered again the next time it is needed.
+    pub fn invalidate(&mut self, index: usize) {
+        if let Some(thumbnail) = self.thumbnails.get_mut(index) {
+            *thumbnail = None;
+        }
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.thumbnails.fill(None);
+    }
+
+    /// Drops all thumbnails if the screen size changed since they were
+    /// rendered, as their size and layout depend on it.
+    pub fn check_screen_size(&mut self) {
+        let screen_size = (screen_width(), screen_height());
+        if screen_size != self.screen_size {
+            self.screen_size = screen_size;
+            self.invalidate_all();
+        }
+    }
+
+    /// Returns the render target of the thumbnail, creating it if needed.
+    pub fn thumbnail_target(&mut self, index: usize) -> RenderTarget {
+        let (width, height) = Self::thumbnail_size();
+        self.thumbnails[index]
+            .get_or_insert_with(|| {
+                let render_target = render_target(width as u32, height as u32);
+                render_target.texture.set_filter(FilterMode::Linear);
+                render_target
+            })
+            .clone()
+    }
+
+    /// Moves the selection by a number of columns and rows.
+    pub fn move_selection(&mut self, columns: isize, rows: isize) {
+        let last = self.thumbnails.len().saturating_sub(1) as isize;
+        let selected = self.selected as isize + columns + rows * Self::COLUMNS as isize;
+        self.select(selected.clamp(0, last) as usize);
+    }
+
+    /// The slide with the thumbnail at the position on the screen.
+    pub fn slide_at(&self, (x, y): (f32, f32)) -> Option<usize> {
+        (0..self.thumbnails.len()).find(|index| {
+            self.thumbnail_rect(*index)
+                .is_some_and(|rect| rect.contains(vec2(x, y)))
+        })
+    }
+
+    pub fn draw(&self, selected_color: Color) {
+        for (index, thumbnail) in self.thumbnails.iter().enumerate() {
+            let (Some(rect), Some(thumbnail)) = (self.thumbnail_rect(index), thumbnail) else {
+                continue;
+            };
+            draw_texture_ex(
+                &thumbnail.texture,
+                rect.x,
+                rect.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(rect.w, rect.h)),
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1., Self::NUMBER_COLOR);
+            if index == self.selected {
+                draw_rectangle_lines(
+                    rect.x - Self::SELECTED_THICKNESS,
+                    rect.y - Self::SELECTED_THICKNESS,
+                    rect.w + Self::SELECTED_THICKNESS * 2.,
+                    rect.h + Self::SELECTED_THICKNESS * 2.,
+                    Self::SELECTED_THICKNESS,
+                    selected_color,
+                );
+            }
+            draw_text(
+                &(index + 1).to_string(),
+                rect.x,
+                rect.y + rect.h + Self::NUMBER_FONT_SIZE * 0.8,
+                Self::NUMBER_FONT_SIZE,
+                Self::NUMBER_COLOR,
+            );
+        }
+    }
+
+    fn select(&mut self, selected: usize) {
+        self.selected = selected.min(self.thumbnails.len().saturating_sub(1));
+        let row = self.selected / Self::COLUMNS;
+        let visible_rows = Self::visible_rows();
+        if row < self.first_row {
+            self.first_row = row;
+        } else if row >= self.first_row + visible_rows {
+            self.first_row = row + 1 - visible_rows;
+        }
+    }
+
+    /// Thumbnails keep the aspect ratio of the screen.
+    fn thumbnail_size() -> (Width, Height) {
+        let width =
+            (screen_width() - Self::SPACING * (Self::COLUMNS + 1) as f32) / Self::COLUMNS as f32;
+        (width, width * screen_height() / screen_width())
+    }
+
+    fn visible_rows() -> usize {
+        let (_, height) = Self::thumbnail_size();
+        (((screen_height() - Self::SPACING) / (height + Self::SPACING)) as usize).max(1)
+    }
+
+    /// Returns the position of the thumbnail, if it is in a visible row.
+    fn thumbnail_rect(&self, index: usize) -> Option<Rect> {
+        let row = (index / Self::COLUMNS).checked_sub(self.first_row)?;
+        if row >= Self::visible_rows() {
+            return None;
+        }
+        let column = index % Self::COLUMNS;
+        let (width, height) = Self::thumbnail_size();
+        Some(Rect::new(
+            Self::SPACING + column as f32 * (width + Self::SPACING),
+            Self::SPACING + row as f32 * (height + Self::SPACING),
+            width,
+            height,
+        ))
+    }
+}
@@ -1,15 +1,39 @@
+pub use crate::ansi::*;
 pub use crate::app_options::*;
+pub use crate::blank_screen::*;
+pub use crate::cargo_project::*;
+pub use crate::chrome::*;
 pub use crate::clipboard::*;
+pub use crate::code_block::*;
 pub use crate::code_box_builder::*;
+pub use crate::code_checker::*;
 pub use crate::codebox::*;
+pub use crate::directive::*;
 pub use crate::drawbox::*;
 pub use crate::executable_code::*;
+pub use crate::execution_limits::*;
+pub use crate::gestures::*;
 pub use crate::hex_color::*;
 pub use crate::imagebox::*;
+pub use crate::keymap::*;
 pub use crate::markdowntoslides::*;
+pub use crate::overview::*;
+pub use crate::presenter_console::*;
+pub use crate::presenter_view::*;
+pub use crate::prompt_box::*;
+pub use crate::recorded_outputs::*;
+pub use crate::runners::*;
+pub use crate::running_code::*;
+pub use crate::search::*;
 pub use crate::shaders::*;
 pub use crate::show_help::*;
+pub use crate::slide_number_prompt::*;
+pub use crate::slide_sync::*;
 pub use crate::slider::*;
+pub use crate::talk_timer::*;
+pub use crate::terminal::*;
+pub use crate::terminal_box::*;
+pub use crate::terminal_screen::*;
 pub use crate::textbox::*;
 pub use crate::theme::*;
 pub use crate::transition::*;
@@ -0,0 +1,200 @@
+use crate::prelude::{PresenterView, Slides};
+#[cfg(target_os = "linux")]
+use std::sync::OnceLock;
+use std::{
+    io::{self, prelude::*},
+    sync::mpsc::{Receiver, Sender, channel},
+    thread,
+};
+
+/// Mode of the terminal before the console changed it.
+#[cfg(target_os = "linux")]
+static ORIGINAL_MODE: OnceLock<libc::termios> = OnceLock::new();
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConsoleCommand {
+    Previous,
+    Next,
+    First,
+    Last,
+    Goto(usize),
+    Quit,
+}
+
+/// Shows the current slide, notes, next slide and timers in the terminal the
+/// slideshow was started from, and moves between slides with keys typed in
+/// that terminal.
+pub struct PresenterConsole {
+    receiver: Receiver<ConsoleCommand>,
+    shown: Option<(usize, u64)>,
+}
+
+impl PresenterConsole {
+    const CLEAR_SCREEN: &'static str = "\x1b[H\x1b[2J";
+    const BEHIND_COLOR: &'static str = "\x1b[33m";
+    const OVERTIME_COLOR: &'static str = "\x1b[31m";
+    const RESET_COLOR: &'static str = "\x1b[0m";
+    const KEYS_HELP: &'static str = "Left/H previous, Right/L/Space next, Up/K first, Down/J last, number and Enter go to slide, Q quit";
+
+    pub fn start() -> Self {
+        #[cfg(target_os = "linux")]
+        Self::read_single_keys();
+        let (sender, receiver) = channel();
+        thread::spawn(move || Self::read_commands(sender));
+        Self {
+            receiver,
+            shown: None,
+        }
+    }
+
+    /// Turns off line buffering, echo and signals, so keys are read as soon
+    /// as they are typed and `Ctrl-C` quits the slideshow the normal way.
+    /// The mode is restored when the console is dropped, and also on panics
+    /// and when the process exits early.
+    #[cfg(target_os = "linux")]
+    fn read_single_keys() {
+        let mut original_mode: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original_mode) } != 0 {
+            return;
+        }
+        if ORIGINAL_MODE.set(original_mode).is_err() {
+            return;
+        }
+        let mut mode = original_mode;
+        mode.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+        mode.c_cc[libc::VMIN] = 1;
+        mode.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &mode) } != 0 {
+            return;
+        }
+        unsafe { libc::atexit(Self::restore_mode_at_exit) };
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            Self::restore_mode();
+            previous_hook(info);
+        }));
+    }
+
+    #[cfg(target_os = "linux")]
+    fn restore_mode() {
+        if let Some(original_mode) = ORIGINAL_MODE.get() {
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original_mode) };
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    extern "C" fn restore_mode_at_exit() {
+        Self::restore_mode();
+    }
+
+    fn read_commands(sender: Sender<ConsoleCommand>) {
+        let mut input = vec![];
+        let mut number = String::new();
+        for byte in io::stdin().lock().bytes().map_while(Result::ok) {
+            input.push(byte);
+            let command = match input.as_slice() {
+                [0x1b] | [0x1b, b'['] => continue,
+                [0x1b, b'[', b'D'] | [b'h' | b'H' | b'p' | b'P'] => Some(ConsoleCommand::Previous),
+                [0x1b, b'[', b'C'] | [b'l' | b'L' | b'n' | b'N' | b' '] => {
+                    Some(ConsoleCommand::Next)
+                }
+                [0x1b, b'[', b'A'] | [0x1b, b'[', b'H'] | [b'k' | b'K'] => {
+                    Some(ConsoleCommand::First)
+                }
+                [0x1b, b'[', b'B'] | [0x1b, b'[', b'F'] | [b'j' | b'J'] => {
+                    Some(ConsoleCommand::Last)
+                }
+                [b'q' | b'Q' | 0x03] => Some(ConsoleCommand::Quit),
+                [digit @ b'0'..=b'9'] => {
+                    number.push(*digit as char);
+                    None
+                }
+                [b'\r' | b'\n' | b'g' | b'G'] => std::mem::take(&mut number)
+                    .parse()
+                    .ok()
+                    .map(ConsoleCommand::Goto),
+                _ => None,
+            };
+            input.clear();
+            if let Some(command) = command {
+                if sender.send(command).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Moves to slides as asked for in the terminal, and shows the slide when
+    /// it or the elapsed seconds have changed. Returns true when quitting
+    /// was asked for.
+    pub fn update(&mut self, slides: &mut Slides) -> bool {
+        while let Ok(command) = self.receiver.try_recv() {
+            match command {
+                ConsoleCommand::Quit => return true,
+                ConsoleCommand::Previous => slides.prev(),
+                ConsoleCommand::Next => slides.next(),
+                ConsoleCommand::First => slides.first(),
+                ConsoleCommand::Last => slides.last(),
+                ConsoleCommand::Goto(slide_number) => slides.goto(slide_number),
+            }
+        }
+        let slide_number = slides.active_slide_number();
+        let shown = Some((slide_number, slides.talk_timer().elapsed() as u64));
+        if self.shown != shown {
+            self.shown = shown;
+            self.draw(slides, slide_number);
+        }
+        false
+    }
+
+    fn draw(&self, slides: &Slides, slide_number: usize) {
+        let mut text = format!(
+            "{}Slide {} / {}: {}\r\n",
+            Self::CLEAR_SCREEN,
+            slide_number,
+            slides.slide_count(),
+            slides.slide_title(slide_number),
+        );
+        let talk_timer = slides.talk_timer();
+        let timer_color = if talk_timer.is_overtime() {
+            Self::OVERTIME_COLOR
+        } else if talk_timer.is_behind(slide_number) {
+            Self::BEHIND_COLOR
+        } else {
+            Self::RESET_COLOR
+        };
+        text.push_str(&format!(
+            "Elapsed {}{}{}",
+            timer_color,
+            talk_timer.text(),
+            Self::RESET_COLOR,
+        ));
+        if let Some(wall_clock) = PresenterView::wall_clock() {
+            text.push_str(&format!("   Clock {}", wall_clock));
+        }
+        text.push_str("\r\n\r\n");
+        for line in slides.notes().lines() {
+            text.push_str(line);
+            text.push_str("\r\n");
+        }
+        if slide_number < slides.slide_count() {
+            text.push_str(&format!(
+                "\r\nNext: {}\r\n",
+                slides.slide_title(slide_number + 1)
+            ));
+        } else {
+            text.push_str("\r\nLast slide\r\n");
+        }
+        text.push_str(&format!("\r\n{}\r\n", Self::KEYS_HELP));
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(text.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+impl Drop for PresenterConsole {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        Self::restore_mode();
+    }
+}
@@ -0,0 +1,191 @@
+use crate::prelude::TalkTimer;
+use macroquad::prelude::*;
+
+/// Layout for the screen of the presenter, with the current slide, a preview
+/// of the next slide, the speaker notes, the talk timer and the wall clock.
+pub struct PresenterView;
+
+impl Default for PresenterView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresenterView {
+    const MARGIN: f32 = 30.;
+    const CURRENT_WIDTH: f32 = 0.6;
+    const LABEL_FONT_SIZE: f32 = 30.;
+    const NOTES_FONT_SIZE: f32 = 36.;
+    const TIME_FONT_SIZE: f32 = 80.;
+    const LINE_SPACING: f32 = 1.3;
+    const TEXT_COLOR: Color = WHITE;
+    const LABEL_COLOR: Color = Color::new(0.6, 0.6, 0.6, 1.);
+    const BACKGROUND_COLOR: Color = Color::new(0.1, 0.1, 0.1, 1.);
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn draw(
+        &self,
+        current: &Texture2D,
+        next: Option<&Texture2D>,
+        notes: &str,
+        slide_number: usize,
+        slide_count: usize,
+        talk_timer: &TalkTimer,
+    ) {
+        clear_background(Self::BACKGROUND_COLOR);
+        let aspect_ratio = screen_height() / screen_width();
+        let current_width = screen_width() * Self::CURRENT_WIDTH;
+        let current_height = current_width * aspect_ratio;
+        let mut vpos = Self::MARGIN + Self::LABEL_FONT_SIZE;
+        Self::draw_label(
+            &format!("Slide {} / {}", slide_number, slide_count),
+            Self::MARGIN,
+            vpos,
+        );
+        vpos += Self::MARGIN / 2.;
+        Self::draw_slide(current, Self::MARGIN, vpos, current_width, current_height);
+
+        let side_hpos = current_width + Self::MARGIN * 2.;
+        let side_width = screen_width() - side_hpos - Self::MARGIN;
+        let mut side_vpos = Self::MARGIN + Self::LABEL_FONT_SIZE;
+        Self::draw_label("Next", side_hpos, side_vpos);
+        side_vpos += Self::MARGIN / 2.;
+        let next_height = side_width * aspect_ratio;
+        match next {
+            Some(texture) => {
+                Self::draw_slide(texture, side_hpos, side_vpos, side_width, next_height)
+            }
+            None => draw_rectangle_lines(
+                side_hpos,
+                side_vpos,
+                side_width,
+                next_height,
+                1.,
+                Self::LABEL_COLOR,
+            ),
+        }
+        side_vpos += next_height + Self::MARGIN + Self::LABEL_FONT_SIZE;
+        Self::draw_label("Elapsed", side_hpos, side_vpos);
+        side_vpos += Self::TIME_FONT_SIZE;
+        let timer_color = talk_timer.color(slide_number);
+        draw_text(
+            &talk_timer.elapsed_text(),
+            side_hpos,
+            side_vpos,
+            Self::TIME_FONT_SIZE,
+            timer_color,
+        );
+        if let Some(remaining) = talk_timer.remaining_text() {
+            side_vpos += Self::NOTES_FONT_SIZE * Self::LINE_SPACING;
+            draw_text(
+                &remaining,
+                side_hpos,
+                side_vpos,
+                Self::NOTES_FONT_SIZE,
+                timer_color,
+            );
+        }
+        if let Some(wall_clock) = Self::wall_clock() {
+            side_vpos += Self::MARGIN + Self::LABEL_FONT_SIZE;
+            Self::draw_label("Clock", side_hpos, side_vpos);
+            side_vpos += Self::TIME_FONT_SIZE;
+            draw_text(
+                &wall_clock,
+                side_hpos,
+                side_vpos,
+                Self::TIME_FONT_SIZE,
+                Self::TEXT_COLOR,
+            );
+        }
+
+        vpos += current_height + Self::MARGIN + Self::LABEL_FONT_SIZE;
+        Self::draw_label("Notes", Self::MARGIN, vpos);
+        for line in Self::wrap_text(notes, current_width) {
+            vpos += Self::NOTES_FONT_SIZE * Self::LINE_SPACING;
+            if vpos > screen_height() {
+                break;
+            }
+            draw_text(
+                &line,
+                Self::MARGIN,
+                vpos,
+                Self::NOTES_FONT_SIZE,
+                Self::TEXT_COLOR,
+            );
+        }
+    }
+
+    fn draw_label(text: &str, hpos: f32, vpos: f32) {
+        draw_text(text, hpos, vpos, Self::LABEL_FONT_SIZE, Self::LABEL_COLOR);
+    }
+
+    fn draw_slide(texture: &Texture2D, hpos: f32, vpos: f32, width: f32, height: f32) {
+        draw_texture_ex(
+            texture,
+            hpos,
+            vpos,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        draw_rectangle_lines(hpos, vpos, width, height, 1., Self::LABEL_COLOR);
+    }
+
+    /// Splits the text into lines that fit the width, keeping line breaks.
+    fn wrap_text(text: &str, width: f32) -> Vec<String> {
+        let mut lines = vec![];
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                if !line.is_empty()
+                    && measure_text(&candidate, None, Self::NOTES_FONT_SIZE as u16, 1.).width
+                        > width
+                {
+                    lines.push(line);
+                    line = word.to_string();
+                } else {
+                    line = candidate;
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// The local time, on platforms where the time zone can be looked up.
+    #[cfg(unix)]
+    pub fn wall_clock() -> Option<String> {
+        let now = unsafe { libc::time(std::ptr::null_mut()) };
+        let mut time: libc::tm = unsafe { std::mem::zeroed() };
+        unsafe { libc::localtime_r(&now, &mut time) };
+        Some(format!("{:02}:{:02}", time.tm_hour, time.tm_min))
+    }
+
+    #[cfg(windows)]
+    pub fn wall_clock() -> Option<String> {
+        #[link(name = "kernel32")]
+        unsafe extern "system" {
+            fn GetLocalTime(time: *mut u16);
+        }
+        // A SYSTEMTIME, which is eight 16 bit fields from year to milliseconds
+        let mut time = [0u16; 8];
+        unsafe { GetLocalTime(time.as_mut_ptr()) };
+        Some(format!("{:02}:{:02}", time[4], time[5]))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn wall_clock() -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,33 @@
+use macroquad::{
+    color::{Color, colors::WHITE},
+    shapes::draw_rectangle,
+    text::{draw_text, measure_text},
+    window::{screen_height, screen_width},
+};
+
+/// A small box in the corner of the screen showing text being typed.
+pub struct PromptBox;
+
+impl PromptBox {
+    const BACKGROUND_COLOR: Color = Color::new(0.1, 0.1, 0.1, 0.8);
+    const FONT_COLOR: Color = WHITE;
+    const MARGIN: f32 = 30.;
+    const PADDING: f32 = 20.;
+    const FONT_SIZE: f32 = 50.;
+
+    pub fn draw(text: &str) {
+        let dimensions = measure_text(text, None, Self::FONT_SIZE as u16, 1.);
+        let width = dimensions.width + Self::PADDING * 2.;
+        let height = Self::FONT_SIZE + Self::PADDING * 2.;
+        let hpos = screen_width() - width - Self::MARGIN;
+        let vpos = screen_height() - height - Self::MARGIN;
+        draw_rectangle(hpos, vpos, width, height, Self::BACKGROUND_COLOR);
+        draw_text(
+            text,
+            hpos + Self::PADDING,
+            vpos + Self::PADDING + dimensions.offset_y,
+            Self::FONT_SIZE,
+            Self::FONT_COLOR,
+        );
+    }
+}
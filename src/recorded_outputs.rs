@@ -0,0 +1,85 @@
+use crate::prelude::{ExecutableCode, ExecutionResult};
+use macroquad::prelude::{debug, load_string};
+use nanoserde::{DeJson, SerJson};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time,
+};
+
+/// The result of running a code block, saved so that it can be shown without
+/// running the code again.
+#[derive(Clone, Debug, DeJson, SerJson)]
+pub struct RecordedOutput {
+    pub language: String,
+    pub code: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+    pub duration: f32,
+}
+
+impl RecordedOutput {
+    pub fn new(code: &ExecutableCode, result: &ExecutionResult) -> Self {
+        Self {
+            language: code.language().to_string(),
+            code: code.executed_code().to_string(),
+            stdout: result.stdout.to_owned(),
+            stderr: result.stderr.to_owned(),
+            exit_code: result.exit_code,
+            duration: result.duration.as_secs_f32(),
+        }
+    }
+
+    pub fn result(&self) -> ExecutionResult {
+        ExecutionResult {
+            stdout: self.stdout.to_owned(),
+            stderr: self.stderr.to_owned(),
+            exit_code: self.exit_code,
+            duration: time::Duration::from_secs_f32(self.duration),
+        }
+    }
+}
+
+/// Outputs recorded from all code blocks in a slideshow, stored in a file next
+/// to the markdown file. Outputs are found by language and code, so they stay
+/// valid when slides are moved around.
+#[derive(Clone, Debug, Default, DeJson, SerJson)]
+pub struct RecordedOutputs {
+    pub outputs: Vec<RecordedOutput>,
+}
+
+impl RecordedOutputs {
+    pub async fn load(recording_path: PathBuf) -> Self {
+        let path = recording_path.as_path().to_str().unwrap().to_owned();
+        debug!("Recorded outputs path: {}", path);
+        match load_string(&path).await {
+            Ok(json) => match RecordedOutputs::deserialize_json(&json) {
+                Ok(recorded_outputs) => recorded_outputs,
+                Err(_) => {
+                    eprintln!("Couldn't parse recorded outputs file: {}", path);
+                    std::process::exit(2);
+                }
+            },
+            Err(_) => RecordedOutputs::default(),
+        }
+    }
+
+    pub fn save(&self, recording_path: &Path) -> std::io::Result<()> {
+        fs::write(recording_path, self.serialize_json())
+    }
+
+    /// Replaces any earlier recording of the same code.
+    pub fn add(&mut self, output: RecordedOutput) {
+        self.outputs.retain(|recorded| {
+            recorded.language != output.language || recorded.code != output.code
+        });
+        self.outputs.push(output);
+    }
+
+    pub fn find(&self, code: &ExecutableCode) -> Option<&RecordedOutput> {
+        self.outputs.iter().find(|recorded| {
+            recorded.language == code.language() && recorded.code == code.executed_code()
+        })
+    }
+}
@@ -0,0 +1,280 @@
+use crate::prelude::{CargoSettings, ExecutionLimits};
+use macroquad::prelude::{debug, load_string};
+use nanoserde::DeJson;
+use std::path::{Path, PathBuf};
+
+/// A command with arguments. The placeholders `{file}`, `{dir}` and
+/// `{binary}` are replaced with the path to the code file, the directory it
+/// is in, and the path to use for a compiled binary.
+#[derive(Clone, Debug, DeJson)]
+pub struct RunnerCommand {
+    pub command: String,
+    #[nserde(default)]
+    pub args: Vec<String>,
+}
+
+impl RunnerCommand {
+    pub fn new(command: &str, args: &[&str]) -> Self {
+        Self {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    pub fn command(&self, file: &Path, dir: &Path, binary: &Path) -> String {
+        Self::replace_placeholders(&self.command, file, dir, binary)
+    }
+
+    pub fn args(&self, file: &Path, dir: &Path, binary: &Path) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| Self::replace_placeholders(arg, file, dir, binary))
+            .collect()
+    }
+
+    fn replace_placeholders(text: &str, file: &Path, dir: &Path, binary: &Path) -> String {
+        text.replace("{file}", &file.to_string_lossy())
+            .replace("{dir}", &dir.to_string_lossy())
+            .replace("{binary}", &binary.to_string_lossy())
+    }
+}
+
+/// Describes how to run code in a code block with one of the languages.
+/// The code is saved to a file with the extension, which is compiled with the
+/// compile command if there is one, and then run with the run command.
+/// With `cargo` set, Rust code with a manifest is built with cargo instead.
+/// Lines starting with the hidden line prefix are run, but not shown.
+#[derive(Clone, Debug, DeJson)]
+pub struct Runner {
+    pub languages: Vec<String>,
+    pub extension: String,
+    pub compile: Option<RunnerCommand>,
+    pub run: RunnerCommand,
+    #[nserde(default)]
+    pub cargo: bool,
+    pub hidden_line_prefix: Option<String>,
+}
+
+impl Runner {
+    pub fn interpreter(languages: &[&str], extension: &str, command: &str) -> Self {
+        Self {
+            languages: languages
+                .iter()
+                .map(|language| language.to_string())
+                .collect(),
+            extension: extension.to_string(),
+            compile: None,
+            run: RunnerCommand::new(command, &["{file}"]),
+            cargo: false,
+            hidden_line_prefix: Some("#~ ".to_string()),
+        }
+    }
+
+    pub fn handles(&self, language: &str) -> bool {
+        self.languages.iter().any(|l| l == language)
+    }
+
+    /// The code as shown on the slide, without hidden lines.
+    pub fn visible_code(&self, code: &str) -> String {
+        code.split_inclusive('\n')
+            .filter(|line| self.hidden_line(line).is_none())
+            .collect()
+    }
+
+    /// The code that is run, with the prefix removed from hidden lines.
+    pub fn executed_code(&self, code: &str) -> String {
+        code.split_inclusive('\n')
+            .map(|line| self.hidden_line(line).unwrap_or_else(|| line.to_string()))
+            .collect()
+    }
+
+    /// Returns the line without prefix if it is hidden. As in rustdoc, a line
+    /// with only the trimmed prefix is a hidden empty line.
+    fn hidden_line(&self, line: &str) -> Option<String> {
+        let prefix = self.hidden_line_prefix.as_ref()?;
+        let content = line.trim_start();
+        let indentation = &line[..line.len() - content.len()];
+        if let Some(rest) = content.strip_prefix(prefix.as_str()) {
+            return Some(format!("{}{}", indentation, rest));
+        }
+        if content.trim_end() == prefix.trim_end() {
+            return Some(content[prefix.trim_end().len()..].to_string());
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug, DeJson)]
+pub struct Runners {
+    #[nserde(default)]
+    pub runners: Vec<Runner>,
+    #[nserde(default)]
+    pub limits: ExecutionLimits,
+    #[nserde(default)]
+    pub cargo: CargoSettings,
+}
+
+impl Default for Runners {
+    fn default() -> Self {
+        Self {
+            runners: vec![
+                Runner::interpreter(&["bash", "sh"], "sh", "bash"),
+                Runner::interpreter(&["python"], "py", "python3"),
+                Runner::interpreter(&["ruby"], "rb", "ruby"),
+                Runner::interpreter(&["perl"], "pl", "perl"),
+                Runner {
+                    languages: vec!["rust".to_string()],
+                    extension: "rs".to_string(),
+                    compile: Some(RunnerCommand::new("rustc", &["-o", "{binary}", "{file}"])),
+                    run: RunnerCommand::new("{binary}", &[]),
+                    cargo: true,
+                    hidden_line_prefix: Some("# ".to_string()),
+                },
+            ],
+            limits: ExecutionLimits::default(),
+            cargo: CargoSettings::default(),
+        }
+    }
+}
+
+impl Runners {
+    /// Runners in the file are used before the default runners, so they can
+    /// both add new languages and override the defaults. Relative paths for
+    /// cargo are resolved from the directory of the file.
+    pub async fn load(runners_path: PathBuf) -> Self {
+        let path = runners_path.as_path().to_str().unwrap().to_owned();
+        debug!("Runners path: {}", path);
+        Self::from_json(load_string(&path).await.ok(), &runners_path)
+    }
+
+    /// Parses the contents of the runners file, if there is one. Used
+    /// directly when there is no window to load files with.
+    pub fn from_json(json: Option<String>, runners_path: &Path) -> Self {
+        let mut runners = match json {
+            Some(json) => match Runners::deserialize_json(&json) {
+                Ok(runners) => runners,
+                Err(_) => {
+                    eprintln!("Couldn't parse runners file: {}", runners_path.display());
+                    std::process::exit(2);
+                }
+            },
+            None => Runners {
+                runners: vec![],
+                ..Runners::default()
+            },
+        };
+        runners.runners.extend(Runners::default().runners);
+        if runners.cargo.directory.is_empty() {
+            if let Some(directory) = runners_path.parent() {
+                runners.cargo.directory = directory.to_string_lossy().to_string();
+            }
+        }
+        runners
+    }
+
+    pub fn find(&self, language: &str) -> Option<&Runner> {
+        self.runners.iter().find(|runner| runner.handles(language))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_placeholders() {
+        let command = RunnerCommand::new("{binary}", &["-o", "{dir}/out", "{file}"]);
+        let (file, dir, binary) = (
+            Path::new("/tmp/code/main.rs"),
+            Path::new("/tmp/code"),
+            Path::new("/tmp/code/main"),
+        );
+        assert_eq!(command.command(file, dir, binary), "/tmp/code/main");
+        assert_eq!(
+            command.args(file, dir, binary),
+            vec!["-o", "/tmp/code/out", "/tmp/code/main.rs"]
+        );
+    }
+
+    #[test]
+    fn uses_default_runners_without_file() {
+        let runners = Runners::from_json(None, Path::new("slides/runners.json"));
+        assert_eq!(runners.find("sh").unwrap().run.command, "bash");
+        assert!(runners.find("rust").unwrap().cargo);
+        assert!(runners.find("cobol").is_none());
+        assert_eq!(runners.cargo.directory, "slides");
+    }
+
+    #[test]
+    fn runners_in_file_come_before_defaults() {
+        let json = r#"{
+            "runners": [
+                {
+                    "languages": ["python", "java"],
+                    "extension": "txt",
+                    "run": { "command": "cat", "args": ["{file}"] }
+                }
+            ]
+        }"#;
+        let runners = Runners::from_json(Some(json.to_string()), Path::new("runners.json"));
+        assert_eq!(runners.find("python").unwrap().run.command, "cat");
+        assert_eq!(runners.find("java").unwrap().extension, "txt");
+        assert_eq!(runners.find("ruby").unwrap().run.command, "ruby");
+        assert!(runners.find("java").unwrap().hidden_line_prefix.is_none());
+    }
+
+    fn rust_runner() -> Runner {
+        Runners::default().find("rust").unwrap().clone()
+    }
+
+    #[test]
+    fn hides_rust_lines_starting_with_hash() {
+        let code = "# fn main() {\n    # let x = 1;\n#\nprintln!(\"{}\", x);\n# }\n";
+        let runner = rust_runner();
+        assert_eq!(runner.visible_code(code), "println!(\"{}\", x);\n");
+        assert_eq!(
+            runner.executed_code(code),
+            "fn main() {\n    let x = 1;\n\nprintln!(\"{}\", x);\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_rust_attributes_visible() {
+        let code = "#[derive(Debug)]\n#![allow(unused)]\nstruct Point;\n";
+        let runner = rust_runner();
+        assert_eq!(runner.visible_code(code), code);
+        assert_eq!(runner.executed_code(code), code);
+    }
+
+    #[test]
+    fn hides_lines_with_default_prefix() {
+        let runner = Runners::default().find("python").unwrap().clone();
+        let code = "#~ import sys\n# a comment\nprint(1)\n";
+        assert_eq!(runner.visible_code(code), "# a comment\nprint(1)\n");
+        assert_eq!(
+            runner.executed_code(code),
+            "import sys\n# a comment\nprint(1)\n"
+        );
+    }
+
+    #[test]
+    fn hides_lines_with_custom_prefix() {
+        let mut runner = Runner::interpreter(&["js"], "js", "node");
+        runner.hidden_line_prefix = Some("//- ".to_string());
+        let code = "//- const x = 1;\n// shown\nconsole.log(x);";
+        assert_eq!(runner.visible_code(code), "// shown\nconsole.log(x);");
+        assert_eq!(
+            runner.executed_code(code),
+            "const x = 1;\n// shown\nconsole.log(x);"
+        );
+    }
+
+    #[test]
+    fn shows_everything_without_prefix() {
+        let mut runner = rust_runner();
+        runner.hidden_line_prefix = None;
+        let code = "# fn main() {}\n";
+        assert_eq!(runner.visible_code(code), code);
+        assert_eq!(runner.executed_code(code), code);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::prelude::*;
+use std::{
+    sync::mpsc::{Receiver, TryRecvError, channel},
+    thread, time,
+};
+
+/// Code executing on a background thread, so the slideshow keeps drawing
+/// while waiting for the result. Output is collected while the process is
+/// running. After `start_typing`, input typed on the output box is sent to
+/// its stdin.
+pub struct RunningCode {
+    pub slide_index: usize,
+    pub code_block_index: usize,
+    control: ExecutionControl,
+    receiver: Receiver<Result<ExecutionResult, ExecutionError>>,
+    output_receiver: Receiver<String>,
+    output: String,
+    console: AnsiText,
+    input: String,
+    typing: bool,
+    changed: bool,
+}
+
+impl RunningCode {
+    pub fn start(
+        slide_index: usize,
+        code_block_index: usize,
+        code: ExecutableCode,
+        timeout: Option<time::Duration>,
+    ) -> Self {
+        let (output_sender, output_receiver) = channel();
+        let control = ExecutionControl::with_output(timeout, output_sender).with_input();
+        let (sender, receiver) = channel();
+        let thread_control = control.clone();
+        thread::spawn(move || {
+            let _ = sender.send(code.execute_with(&thread_control));
+        });
+        Self {
+            slide_index,
+            code_block_index,
+            control,
+            receiver,
+            output_receiver,
+            output: String::new(),
+            console: AnsiText::new(),
+            input: String::new(),
+            typing: false,
+            changed: true,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.control.cancel();
+    }
+
+    pub fn elapsed(&self) -> time::Duration {
+        self.control.elapsed()
+    }
+
+    /// All output from stdout and stderr received so far.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// The last lines of output. While typing, they are followed by the
+    /// input that hasn't been sent yet and a cursor, after any prompt on the
+    /// last line of output.
+    pub fn console_lines(&self, count: usize) -> Vec<Vec<AnsiSegment>> {
+        let mut lines = self.console.last_lines(count);
+        match lines.last_mut() {
+            Some(line) if self.typing => line.push(AnsiSegment {
+                text: format!("{}_", self.input),
+                style: AnsiStyle::default(),
+            }),
+            Some(line) if line.is_empty() => {
+                lines.pop();
+            }
+            _ => (),
+        }
+        lines
+    }
+
+    /// Typed text is used as input to the program, instead of as shortcuts.
+    pub fn start_typing(&mut self) {
+        self.typing = true;
+        self.changed = true;
+    }
+
+    pub fn stop_typing(&mut self) {
+        self.typing = false;
+        self.changed = true;
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    pub fn push_input(&mut self, c: char) {
+        self.input.push(c);
+        self.changed = true;
+    }
+
+    pub fn pop_input(&mut self) {
+        self.changed |= self.input.pop().is_some();
+    }
+
+    /// Sends the input as a line to the program. The line is added to the
+    /// output, the way a terminal echoes it.
+    pub fn send_input(&mut self) {
+        let line = format!("{}\n", std::mem::take(&mut self.input));
+        if self.control.send_input(&line) {
+            self.push_output(&line);
+        }
+        self.changed = true;
+    }
+
+    /// Sends the input typed so far and closes stdin of the program, which
+    /// reads it as the end of input. Typing stops, as there is nothing more
+    /// to send.
+    pub fn close_input(&mut self) {
+        let text = std::mem::take(&mut self.input);
+        if !text.is_empty() && self.control.send_input(&text) {
+            self.push_output(&text);
+        }
+        self.control.close_input();
+        self.stop_typing();
+    }
+
+    /// Collects output sent since the last call, returns true if there was
+    /// any new output or input.
+    pub fn read_output(&mut self) -> bool {
+        while let Ok(text) = self.output_receiver.try_recv() {
+            self.push_output(&text);
+        }
+        std::mem::take(&mut self.changed)
+    }
+
+    fn push_output(&mut self, text: &str) {
+        self.output.push_str(text);
+        self.console.push(text);
+        self.changed = true;
+    }
+
+    /// Returns the result once the execution has finished.
+    pub fn try_result(&self) -> Option<Result<ExecutionResult, ExecutionError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(ExecutionError::InputOutput)),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::prelude::PromptBox;
+
+/// Text search across all slides. The query is typed in a prompt, and the
+/// number of matches on each slide is kept so that the matches can be
+/// stepped through one at a time. Matches are given as the index of the
+/// slide and the index of the match on that slide. A finished search stays
+/// active until it is cleared, even without matches.
+#[derive(Default)]
+pub struct Search {
+    query: String,
+    typing: bool,
+    active: bool,
+    matches: Vec<(usize, usize)>,
+    current: Option<(usize, usize)>,
+}
+
+impl Search {
+    pub fn start(&mut self) {
+        self.query.clear();
+        self.typing = true;
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+    }
+
+    pub fn finish(&mut self) {
+        self.typing = false;
+        self.active = !self.query.is_empty();
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.typing = false;
+        self.active = false;
+        self.matches.clear();
+        self.current = None;
+    }
+
+    /// Sets the slides with matches, with the number of matches on each.
+    pub fn set_matches(&mut self, matches: Vec<(usize, usize)>) {
+        self.matches = matches;
+        self.current = None;
+    }
+
+    /// Moves to the first match, starting at the given slide.
+    pub fn first_match_from(&mut self, slide: usize) -> Option<(usize, usize)> {
+        self.current = self
+            .matches
+            .iter()
+            .find(|(index, _)| *index >= slide)
+            .or(self.matches.first())
+            .map(|(index, _)| (*index, 0));
+        self.current
+    }
+
+    /// Moves to the match after the current one on the given slide, or to
+    /// the first match on a later slide, wrapping around to the first one.
+    pub fn next_match(&mut self, slide: usize) -> Option<(usize, usize)> {
+        self.current = match self.current {
+            Some((index, current)) if index == slide && current + 1 < self.count(slide) => {
+                Some((slide, current + 1))
+            }
+            _ => self
+                .matches
+                .iter()
+                .find(|(index, _)| *index > slide)
+                .or(self.matches.first())
+                .map(|(index, _)| (*index, 0)),
+        };
+        self.current
+    }
+
+    /// Moves to the match before the current one on the given slide, or to
+    /// the last match on an earlier slide, wrapping around to the last one.
+    pub fn previous_match(&mut self, slide: usize) -> Option<(usize, usize)> {
+        self.current = match self.current {
+            Some((index, current)) if index == slide && current > 0 => Some((slide, current - 1)),
+            _ => self
+                .matches
+                .iter()
+                .rev()
+                .find(|(index, _)| *index < slide)
+                .or(self.matches.last())
+                .map(|(index, count)| (*index, count - 1)),
+        };
+        self.current
+    }
+
+    /// Number of matches on the slide.
+    fn count(&self, slide: usize) -> usize {
+        self.matches
+            .iter()
+            .find(|(index, _)| *index == slide)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    pub fn draw(&self) {
+        if self.typing {
+            PromptBox::draw(&format!("Search: {}_", self.query));
+        } else if self.active && self.matches.is_empty() {
+            PromptBox::draw(&format!("No matches: {}", self.query));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: &str, matches: Vec<(usize, usize)>) -> Search {
+        let mut search = Search::default();
+        search.start();
+        query.chars().for_each(|c| search.push(c));
+        search.finish();
+        search.set_matches(matches);
+        search
+    }
+
+    #[test]
+    fn stays_active_without_matches_until_cleared() {
+        let mut search = search("missing", vec![]);
+        assert!(search.is_active());
+        assert_eq!(search.first_match_from(0), None);
+        search.clear();
+        assert!(!search.is_active());
+    }
+
+    #[test]
+    fn empty_query_is_not_active() {
+        assert!(!search("", vec![]).is_active());
+    }
+
+    #[test]
+    fn steps_through_matches_on_each_slide() {
+        let mut search = search("rust", vec![(1, 2), (4, 1)]);
+        assert_eq!(search.first_match_from(2), Some((4, 0)));
+        assert_eq!(search.next_match(4), Some((1, 0)));
+        assert_eq!(search.next_match(1), Some((1, 1)));
+        assert_eq!(search.next_match(1), Some((4, 0)));
+        assert_eq!(search.previous_match(4), Some((1, 1)));
+        assert_eq!(search.previous_match(1), Some((1, 0)));
+        assert_eq!(search.previous_match(1), Some((4, 0)));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::prelude::Keymap;
 use macroquad::{
     color::{Color, colors::WHITE},
     shapes::draw_rectangle,
@@ -7,11 +8,12 @@ use macroquad::{
 
 pub struct ShowHelp {
     pub showing: bool,
+    help_text: String,
 }
 
 impl Default for ShowHelp {
     fn default() -> Self {
-        Self::new()
+        Self::new(Keymap::default().help_text())
     }
 }
 
@@ -21,10 +23,11 @@ impl ShowHelp {
     const MARGIN: f32 = 60.;
     const FONT_SIZE: f32 = 70.;
     const LINE_OFFSET: f32 = 10.;
-    const HELP_TEXT: &'static str = include_str!("helptext.txt");
-
-    pub fn new() -> Self {
-        Self { showing: false }
+    pub fn new(help_text: String) -> Self {
+        Self {
+            showing: false,
+            help_text,
+        }
     }
 
     pub fn draw(&self) {
@@ -40,7 +43,7 @@ impl ShowHelp {
         );
 
         let mut offset_y = Self::MARGIN + Self::FONT_SIZE + Self::LINE_OFFSET;
-        for line in Self::HELP_TEXT.split('\n') {
+        for line in self.help_text.split('\n') {
             offset_y = self.draw_line(Self::MARGIN * 2., offset_y, line);
         }
     }
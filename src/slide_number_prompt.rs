@@ -0,0 +1,53 @@
+use crate::prelude::PromptBox;
+
+/// Collects the digits of a slide number to jump to, and shows them in a
+/// small box in the corner of the screen.
+pub struct SlideNumberPrompt {
+    number: String,
+}
+
+impl Default for SlideNumberPrompt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlideNumberPrompt {
+    const MAX_DIGITS: usize = 5;
+
+    pub fn new() -> Self {
+        Self {
+            number: String::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.number.is_empty()
+    }
+
+    pub fn push_digit(&mut self, digit: char) {
+        if digit.is_ascii_digit() && self.number.len() < Self::MAX_DIGITS {
+            self.number.push(digit);
+        }
+    }
+
+    pub fn remove_digit(&mut self) {
+        self.number.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.number.clear();
+    }
+
+    /// Returns the slide number that was typed, and empties the prompt.
+    pub fn take(&mut self) -> Option<usize> {
+        std::mem::take(&mut self.number).parse().ok()
+    }
+
+    pub fn draw(&self) {
+        if !self.is_active() {
+            return;
+        }
+        PromptBox::draw(&format!("Go to slide: {}", self.number));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::prelude::Slides;
+use macroquad::prelude::{debug, get_time};
+use std::{fs, path::PathBuf};
+
+/// Shares the current slide with other instances showing the same slides,
+/// through a file next to the slides file. Moving to another slide in any of
+/// the instances moves all of them there.
+pub struct SlideSync {
+    path: PathBuf,
+    shared: Option<usize>,
+    last_poll: f64,
+}
+
+impl SlideSync {
+    const POLL_INTERVAL: f64 = 0.2;
+
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            shared: None,
+            last_poll: 0.,
+        }
+    }
+
+    /// Shares the current slide if it was changed here, or goes to the slide
+    /// shared by another instance. Starts at the shared slide, if there is one.
+    /// A blanked screen doesn't follow the other instances until it shows the
+    /// slide again.
+    pub fn update(&mut self, slides: &mut Slides) {
+        if slides.is_screen_blanked() {
+            return;
+        }
+        let current = slides.active_slide_number();
+        if self.shared.is_some_and(|shared| shared != current) {
+            self.share(current);
+            return;
+        }
+        if get_time() - self.last_poll < Self::POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = get_time();
+        match self.read() {
+            Some(shared) if Some(shared) != self.shared => {
+                slides.goto(shared);
+                self.shared = Some(slides.active_slide_number());
+            }
+            Some(_) => (),
+            None if self.shared.is_none() => self.share(current),
+            None => (),
+        }
+    }
+
+    fn read(&self) -> Option<usize> {
+        fs::read_to_string(&self.path).ok()?.trim().parse().ok()
+    }
+
+    fn share(&mut self, slide_number: usize) {
+        if let Err(err) = fs::write(&self.path, slide_number.to_string()) {
+            debug!("Couldn't write slide sync file {:?}: {}", self.path, err);
+        }
+        self.shared = Some(slide_number);
+    }
+}
+
+impl Drop for SlideSync {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
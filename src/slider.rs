@@ -3,6 +3,7 @@ use crate::prelude::*;
 use macroquad::prelude::*;
 use nanoserde::DeJson;
 use regex::Regex;
+use std::collections::HashMap;
 
 #[derive(Copy, Clone, Debug, DeJson)]
 #[allow(non_camel_case_types)]
@@ -21,7 +22,13 @@ impl Default for DrawAlignment {
 #[derive(Clone)]
 pub struct Slide {
     pub draw_boxes: Vec<DrawBox>,
-    pub code_block: Option<ExecutableCode>,
+    pub code_blocks: Vec<CodeBlock>,
+    pub title: String,
+    pub notes: String,
+    pub hidden_chrome: HiddenChrome,
+    pub time_budget: Option<Duration>,
+    focused_code_block: usize,
+    focus_color: Color,
     align: DrawAlignment,
     horizontal_offset: Hpos,
     background_texture: Option<Texture2D>,
@@ -31,14 +38,21 @@ pub struct Slide {
 impl Slide {
     pub fn new(
         draw_boxes: Vec<DrawBox>,
-        code_block: Option<ExecutableCode>,
+        code_blocks: Vec<CodeBlock>,
+        focus_color: Color,
         align: DrawAlignment,
         horizontal_offset: Hpos,
         background_path: Option<String>,
     ) -> Self {
         Self {
             draw_boxes,
-            code_block,
+            code_blocks,
+            title: String::new(),
+            notes: String::new(),
+            hidden_chrome: HiddenChrome::default(),
+            time_budget: None,
+            focused_code_block: 0,
+            focus_color,
             align,
             horizontal_offset,
             background_texture: None,
@@ -49,7 +63,13 @@ impl Slide {
     pub fn empty(align: DrawAlignment, horizontal_offset: Hpos) -> Self {
         Self {
             draw_boxes: vec![],
-            code_block: None,
+            code_blocks: vec![],
+            title: String::new(),
+            notes: String::new(),
+            hidden_chrome: HiddenChrome::default(),
+            time_budget: None,
+            focused_code_block: 0,
+            focus_color: WHITE,
             align,
             horizontal_offset,
             background_texture: None,
@@ -60,12 +80,94 @@ impl Slide {
     pub fn draw(&self, default_background: Option<Texture2D>) {
         self.draw_background(default_background);
         let mut new_position: Vpos = 0.;
-        for draw_box in self.draw_boxes.iter() {
+        for (index, draw_box) in self.draw_boxes.iter().enumerate() {
             let hpos = self.horizontal_position(draw_box.width_with_padding());
-            new_position = draw_box.draw(hpos, new_position);
+            let vpos = new_position;
+            new_position = draw_box.draw(hpos, vpos);
+            if let Some(code_block_index) = self.code_block_index_at(index) {
+                if let DrawBox::Code(code_box) = draw_box {
+                    if self.code_blocks.len() > 1 && code_block_index == self.focused_code_block {
+                        code_box.draw_outline(hpos, vpos, self.focus_color);
+                    }
+                    if self.code_blocks[code_block_index].running {
+                        code_box.draw_spinner(hpos, vpos);
+                    }
+                }
+                if let Some(output) = &self.code_blocks[code_block_index].output {
+                    let hpos = self.horizontal_position(output.width_with_padding());
+                    new_position = output.draw(hpos, new_position);
+                }
+            }
+            if let DrawBox::Terminal(terminal_box) = draw_box {
+                if terminal_box.focused {
+                    terminal_box
+                        .code_box()
+                        .draw_outline(hpos, vpos, self.focus_color);
+                }
+            }
+        }
+    }
+
+    /// Highlights text matching the search query, returns the number of
+    /// matches on the slide.
+    pub fn highlight(&mut self, query: &str, color: Color) -> usize {
+        self.draw_boxes
+            .iter_mut()
+            .map(|draw_box| draw_box.highlight(query, color))
+            .sum()
+    }
+
+    /// Marks the match with the index as the current one, or removes the
+    /// mark with None.
+    pub fn select_highlight(&mut self, mut index: Option<usize>) {
+        for draw_box in self.draw_boxes.iter_mut() {
+            index = draw_box.select_highlight(index);
+        }
+    }
+
+    pub fn terminal_box_index(&self) -> Option<usize> {
+        self.draw_boxes
+            .iter()
+            .position(|draw_box| matches!(draw_box, DrawBox::Terminal(_)))
+    }
+
+    pub fn terminal_box_mut(&mut self, draw_box_index: usize) -> Option<&mut TerminalBox> {
+        match self.draw_boxes.get_mut(draw_box_index) {
+            Some(DrawBox::Terminal(terminal_box)) => Some(terminal_box),
+            _ => None,
+        }
+    }
+
+    pub fn focused_code_block(&self) -> Option<&CodeBlock> {
+        self.code_blocks.get(self.focused_code_block)
+    }
+
+    pub fn focused_code_block_index(&self) -> usize {
+        self.focused_code_block
+    }
+
+    pub fn focused_code_block_mut(&mut self) -> Option<&mut CodeBlock> {
+        self.code_blocks.get_mut(self.focused_code_block)
+    }
+
+    pub fn focus_code_block(&mut self, index: usize) {
+        if index < self.code_blocks.len() {
+            self.focused_code_block = index;
         }
     }
 
+    pub fn focus_next_code_block(&mut self) {
+        if !self.code_blocks.is_empty() {
+            self.focused_code_block = (self.focused_code_block + 1) % self.code_blocks.len();
+        }
+    }
+
+    fn code_block_index_at(&self, draw_box_index: usize) -> Option<usize> {
+        self.code_blocks
+            .iter()
+            .position(|code_block| code_block.draw_box_index == draw_box_index)
+    }
+
     pub async fn load_images(&mut self) {
         self.load_background_image().await;
         for draw_box in &mut self.draw_boxes.iter_mut() {
@@ -80,10 +182,6 @@ impl Slide {
         }
     }
 
-    pub fn add_code_box(&mut self, draw_box: CodeBox) {
-        self.draw_boxes.push(DrawBox::Code(draw_box));
-    }
-
     pub fn add_text_box(&mut self, draw_box: TextBox) {
         self.draw_boxes.push(DrawBox::Text(draw_box));
     }
@@ -116,52 +214,90 @@ pub struct Slides {
     slides: Vec<Slide>,
     theme: Theme,
     code_box_builder: CodeBoxBuilder,
+    chrome: Chrome,
     background: Option<Texture2D>,
     automatic: Duration,
     active_slide: usize,
     time: Duration,
     render_target: RenderTarget,
+    next_render_target: Option<RenderTarget>,
     pub previous_texture: Option<Texture2D>,
     demo_transitions: bool,
     transitioner: Option<Transitioner>,
+    code_timeout: Option<std::time::Duration>,
+    running_code: Option<RunningCode>,
+    recorded_outputs: RecordedOutputs,
+    enable_terminals: bool,
+    terminals: Vec<Terminal>,
+    focused_terminal: Option<usize>,
+    overview: Overview,
+    search: Search,
+    talk_timer: TalkTimer,
+    blank_screen: BlankScreen,
 }
 
 impl Slides {
+    const SEARCH_HIGHLIGHT_ALPHA: f32 = 0.45;
+
     fn from_slides(
         slides: Vec<Slide>,
         theme: Theme,
         code_box_builder: CodeBoxBuilder,
+        chrome: Chrome,
         background: Option<Texture2D>,
         automatic: Duration,
         demo_transitions: bool,
         transitioner: Option<Transitioner>,
         active_slide: usize,
+        code_timeout: Duration,
+        recorded_outputs: RecordedOutputs,
+        enable_terminals: bool,
+        talk_length: Option<Duration>,
     ) -> Slides {
         let active_slide = active_slide.min(slides.len()).max(1) - 1;
+        let slide_count = slides.len();
+        let time_budgets = slides.iter().map(|slide| slide.time_budget).collect();
         Slides {
             slides,
             theme,
             code_box_builder,
+            chrome,
             background,
             automatic,
             time: 0.,
             active_slide,
             render_target: Self::render_target(),
+            next_render_target: None,
             previous_texture: None,
             demo_transitions,
             transitioner,
+            code_timeout: (code_timeout > 0.)
+                .then(|| std::time::Duration::from_secs_f32(code_timeout)),
+            running_code: None,
+            recorded_outputs,
+            enable_terminals,
+            terminals: vec![],
+            focused_terminal: None,
+            overview: Overview::new(slide_count),
+            search: Search::default(),
+            talk_timer: TalkTimer::new(talk_length, time_budgets),
+            blank_screen: BlankScreen::new(),
         }
     }
 
     pub async fn load(options: AppOptions, theme: Theme) -> Self {
         let path = options.slides_path();
-        let markdown = match load_string(&path.to_str().unwrap()).await {
-            Ok(text) => Self::sanitize_markdown(text),
-            Err(_) => {
-                eprintln!("Couldn't parse markdown document: {:?}", path);
-                std::process::exit(1);
-            }
-        };
+        let ((markdown, directives), front_matter) =
+            match load_string(&path.to_str().unwrap()).await {
+                Ok(text) => (
+                    Self::sanitize_markdown(text.clone()),
+                    Self::front_matter(&text),
+                ),
+                Err(_) => {
+                    eprintln!("Couldn't parse markdown document: {:?}", path);
+                    std::process::exit(1);
+                }
+            };
         debug!("Sanitized markdown:\n{}", markdown);
 
         let font_text = load_ttf_font(&theme.font)
@@ -185,29 +321,49 @@ impl Slides {
             None => None,
         };
 
+        let code_box_builder = match CodeBoxBuilder::new(
+            theme.clone(),
+            &options.syntaxes_path(),
+            font_code.clone(),
+            font_bold.clone(),
+            font_italic.clone(),
+        ) {
+            Ok(code_box_builder) => code_box_builder,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
+        };
+
+        let chrome = Chrome::new(
+            theme.clone(),
+            font_text.clone(),
+            front_matter.get("title").cloned(),
+        );
         let mut slides = MarkdownToSlides::new(
             theme.clone(),
             font_text,
             font_bold.clone(),
             font_italic.clone(),
             font_code.clone(),
+            code_box_builder.clone(),
+            Runners::load(options.runners_path()).await,
         )
-        .parse(markdown);
+        .parse(markdown, &directives);
 
         // Load images for all slides
         for slide in &mut slides.iter_mut() {
             slide.load_images().await;
         }
 
-        let code_box_builder = CodeBoxBuilder::new(
-            theme.clone(),
-            font_code.clone(),
-            font_bold.clone(),
-            font_italic.clone(),
-        );
+        // The talk length is given in minutes
+        let talk_length = options
+            .talk_length
+            .or_else(|| front_matter.get("talk_length")?.parse().ok())
+            .map(|minutes: Duration| minutes * 60.);
 
         let transitioner = match theme.transition {
-            Some(transition) => Some(Transitioner::load(options.assets, transition, 0.1).await),
+            Some(transition) => Some(Transitioner::load(&options.assets, transition, 0.1).await),
             None => None,
         };
 
@@ -215,22 +371,46 @@ impl Slides {
             slides,
             theme.clone(),
             code_box_builder,
+            chrome,
             background,
             options.automatic,
             options.demo_transitions,
             transitioner,
             options.number.try_into().unwrap_or(1),
+            options.code_timeout,
+            RecordedOutputs::load(options.recording_path()).await,
+            options.enable_code_execution,
+            talk_length,
         )
     }
 
-    pub fn sanitize_markdown(text: String) -> String {
-        let no_comments = Self::strip_comments(text);
-        Self::strip_yaml_header(no_comments)
+    /// Removes comments and the YAML header. Directives in comments are
+    /// returned separately, see [`Directive::extract`].
+    pub fn sanitize_markdown(text: String) -> (String, Vec<Directive>) {
+        let (markdown, directives) = Directive::extract(&text);
+        (Self::strip_yaml_header(markdown), directives)
     }
 
-    pub fn strip_comments(text: String) -> String {
-        let re = Regex::new(r"(?sm)<!--.*?--\s*>").unwrap();
-        re.replace_all(&text, "").to_string()
+    /// Values in the YAML header at the start of the slides, only lines with
+    /// `key: value` are read.
+    pub fn front_matter(text: &str) -> HashMap<String, String> {
+        let re =
+            Regex::new(r"(?s)\A---(\r\n?|\n)((\w+?): (.+?)(\r\n?|\n))+?---(\r\n?|\n)").unwrap();
+        re.find(text)
+            .map(|header| {
+                header
+                    .as_str()
+                    .lines()
+                    .filter_map(|line| line.split_once(": "))
+                    .map(|(key, value)| {
+                        (
+                            key.trim().to_string(),
+                            value.trim().trim_matches('"').to_string(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     pub fn strip_yaml_header(text: String) -> String {
@@ -240,30 +420,90 @@ impl Slides {
     }
 
     pub fn next(&mut self) {
-        if self.active_slide < (self.slides.len() - 1) {
+        if self.restore_instead_of_moving() {
+            return;
+        }
+        if self.active_slide + 1 < self.slides.len() {
             self.set_active_slide(self.active_slide + 1);
         }
     }
 
     pub fn prev(&mut self) {
+        if self.restore_instead_of_moving() {
+            return;
+        }
         if self.active_slide > 0 {
             self.set_active_slide(self.active_slide - 1);
         }
     }
 
     pub fn first(&mut self) {
+        if self.restore_instead_of_moving() {
+            return;
+        }
         if self.active_slide > 0 {
             self.set_active_slide(0);
         }
     }
 
     pub fn last(&mut self) {
-        if self.active_slide < (self.slides.len() - 1) {
+        if self.restore_instead_of_moving() {
+            return;
+        }
+        if self.active_slide + 1 < self.slides.len() {
             self.set_active_slide(self.slides.len() - 1);
         }
     }
 
+    /// Jumps to a slide by its number, starting at 1. Numbers past the last
+    /// slide go to the last slide.
+    pub fn goto(&mut self, slide_number: usize) {
+        if self.slides.is_empty() || self.restore_instead_of_moving() {
+            return;
+        }
+        let active_slide = slide_number.clamp(1, self.slides.len()) - 1;
+        if active_slide != self.active_slide {
+            self.set_active_slide(active_slide);
+        }
+    }
+
+    /// Number of the current slide, starting at 1.
+    pub fn active_slide_number(&self) -> usize {
+        self.active_slide + 1
+    }
+
+    pub fn slide_count(&self) -> usize {
+        self.slides.len()
+    }
+
+    /// Title of a slide by its number, starting at 1. Empty if the slide
+    /// has no heading.
+    pub fn slide_title(&self, slide_number: usize) -> &str {
+        slide_number
+            .checked_sub(1)
+            .and_then(|index| self.slides.get(index))
+            .map(|slide| slide.title.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Speaker notes of the current slide.
+    pub fn notes(&self) -> &str {
+        self.current_slide()
+            .map(|slide| slide.notes.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Moving to another slide while the screen is blanked only shows the
+    /// slide again, on the same position. Returns true if the screen was
+    /// blanked.
+    fn restore_instead_of_moving(&mut self) -> bool {
+        let blanked = self.blank_screen.is_blanked();
+        self.blank_screen.restore();
+        blanked
+    }
+
     fn set_active_slide(&mut self, active_slide: usize) {
+        self.unfocus_terminal();
         self.active_slide = active_slide;
         self.time = 0.;
         self.update_previous_texture();
@@ -281,21 +521,363 @@ impl Slides {
         }
     }
 
+    /// Automatic switching of slides is paused while the screen is blanked.
     pub fn update(&mut self, delta: Duration) {
-        if self.automatic > 0. && self.time > self.automatic {
-            self.next();
-        } else {
-            self.time += delta;
+        if !self.blank_screen.is_blanked() {
+            if self.automatic > 0. && self.time > self.automatic {
+                self.next();
+            } else {
+                self.time += delta;
+            }
         }
+        self.blank_screen.update(delta);
         if let Some(transitioner) = &mut self.transitioner {
             transitioner.update(delta);
         }
+        self.talk_timer.update(delta);
+        self.update_running_code();
+        self.update_terminals();
+        if self.overview.showing {
+            self.render_thumbnails();
+        }
+    }
+
+    /// Shows new output from terminals on their slides.
+    fn update_terminals(&mut self) {
+        for (index, terminal) in self.terminals.iter_mut().enumerate() {
+            if terminal.update() {
+                let code_box = self
+                    .code_box_builder
+                    .build_screen_box(terminal.screen(), self.focused_terminal == Some(index));
+                if let Some(terminal_box) = self
+                    .slides
+                    .get_mut(terminal.slide_index)
+                    .and_then(|slide| slide.terminal_box_mut(terminal.draw_box_index))
+                {
+                    terminal_box.set_code_box(code_box);
+                    self.overview.invalidate(terminal.slide_index);
+                }
+            }
+        }
+    }
+
+    /// Focuses the terminal on the current slide, starting it the first
+    /// time. Only possible when code execution is enabled.
+    pub fn focus_terminal(&mut self) {
+        if !self.enable_terminals {
+            return;
+        }
+        let Some(draw_box_index) = self
+            .slides
+            .get(self.active_slide)
+            .and_then(Slide::terminal_box_index)
+        else {
+            return;
+        };
+        let index = match self.terminals.iter().position(|terminal| {
+            terminal.slide_index == self.active_slide && terminal.draw_box_index == draw_box_index
+        }) {
+            Some(index) => index,
+            None => {
+                let slide = &mut self.slides[self.active_slide];
+                let terminal_box = slide.terminal_box_mut(draw_box_index).unwrap();
+                match Terminal::start(
+                    self.active_slide,
+                    draw_box_index,
+                    terminal_box.command(),
+                    self.theme.terminal_rows,
+                    self.theme.terminal_columns,
+                ) {
+                    Ok(terminal) => {
+                        self.terminals.push(terminal);
+                        self.terminals.len() - 1
+                    }
+                    Err(err) => {
+                        let mut screen = TerminalScreen::new(
+                            self.theme.terminal_rows,
+                            self.theme.terminal_columns,
+                        );
+                        screen.write(format!("Couldn't start terminal: {}", err).as_bytes());
+                        terminal_box
+                            .set_code_box(self.code_box_builder.build_screen_box(&screen, false));
+                        return;
+                    }
+                }
+            }
+        };
+        self.focused_terminal = Some(index);
+        self.set_terminal_focus(index, true);
+    }
+
+    pub fn unfocus_terminal(&mut self) {
+        if let Some(index) = self.focused_terminal.take() {
+            self.set_terminal_focus(index, false);
+        }
+    }
+
+    fn set_terminal_focus(&mut self, index: usize, focused: bool) {
+        let terminal = &self.terminals[index];
+        let code_box = self
+            .code_box_builder
+            .build_screen_box(terminal.screen(), focused);
+        if let Some(terminal_box) = self
+            .slides
+            .get_mut(terminal.slide_index)
+            .and_then(|slide| slide.terminal_box_mut(terminal.draw_box_index))
+        {
+            terminal_box.focused = focused;
+            terminal_box.set_code_box(code_box);
+        }
+    }
+
+    /// Key presses go to the focused terminal instead of being used as
+    /// shortcuts.
+    pub fn is_terminal_focused(&self) -> bool {
+        self.focused_terminal.is_some()
+    }
+
+    pub fn send_to_terminal(&mut self, bytes: &[u8]) {
+        if let Some(index) = self.focused_terminal {
+            self.terminals[index].send(bytes);
+        }
+    }
+
+    /// Shows output from running code as it arrives, and the final output
+    /// with exit status when the code has finished. The result is checked
+    /// before the output, so output sent just before the end isn't missed.
+    fn update_running_code(&mut self) {
+        let Some(running_code) = &mut self.running_code else {
+            return;
+        };
+        let result = running_code.try_result();
+        let updated = running_code.read_output();
+        let Some(code_block) = self
+            .slides
+            .get_mut(running_code.slide_index)
+            .and_then(|slide| slide.code_blocks.get_mut(running_code.code_block_index))
+        else {
+            if result.is_some() {
+                self.running_code = None;
+            }
+            return;
+        };
+        if updated || result.is_some() {
+            self.overview.invalidate(running_code.slide_index);
+        }
+        match result {
+            None if updated => {
+                code_block.output = Some(self.code_box_builder.build_console_box(
+                    &running_code.console_lines(self.code_box_builder.output_max_lines()),
+                ));
+            }
+            None => (),
+            Some(result) => {
+                let finished = match result {
+                    Ok(execution_result) => FinishedRun::Result(execution_result),
+                    Err(err) => FinishedRun::Error {
+                        output: running_code.output().to_string(),
+                        status: CodeBoxBuilder::error_status(&err, running_code.elapsed()),
+                    },
+                };
+                let code_box = self.code_box_builder.build_finished_box(&finished, 0);
+                code_block.finish(finished, code_box);
+                self.running_code = None;
+            }
+        }
     }
 
     pub fn draw(&self) {
         self.set_camera();
         clear_background(self.theme.background_color);
-        self.draw_slide();
+        if self.overview.showing {
+            self.overview.draw(self.theme.code_focus_color);
+        } else {
+            self.draw_slide();
+        }
+    }
+
+    pub fn is_showing_overview(&self) -> bool {
+        self.overview.showing
+    }
+
+    /// Shows thumbnails of all slides, with the current slide selected. The
+    /// current slide is rendered again, as it may have changed since its
+    /// thumbnail was rendered.
+    pub fn open_overview(&mut self) {
+        self.overview.invalidate(self.active_slide);
+        self.render_thumbnails();
+        self.overview.open(self.active_slide);
+    }
+
+    pub fn close_overview(&mut self) {
+        self.overview.close();
+    }
+
+    pub fn move_overview_selection(&mut self, columns: isize, rows: isize) {
+        self.overview.move_selection(columns, rows);
+    }
+
+    /// Selects the slide at the position on the screen, returns true if there
+    /// was a slide there.
+    pub fn select_overview_slide_at(&mut self, position: (f32, f32)) -> bool {
+        match self.overview.slide_at(position) {
+            Some(index) => {
+                self.overview.open(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Closes the overview and goes to the selected slide.
+    pub fn goto_overview_selection(&mut self) {
+        self.overview.close();
+        self.goto(self.overview.selected() + 1);
+    }
+
+    pub fn start_search(&mut self) {
+        self.search.start();
+    }
+
+    pub fn is_typing_search(&self) -> bool {
+        self.search.is_typing()
+    }
+
+    /// True after a search, until it is cleared, even without matches.
+    pub fn is_search_active(&self) -> bool {
+        self.search.is_active()
+    }
+
+    pub fn add_search_input(&mut self, c: char) {
+        self.search.push(c);
+    }
+
+    pub fn remove_search_input(&mut self) {
+        self.search.pop();
+    }
+
+    /// Highlights the matches on all slides, and goes to the first match,
+    /// starting at the current slide.
+    pub fn finish_search(&mut self) {
+        self.search.finish();
+        self.highlight_search();
+        let current = self.search.first_match_from(self.active_slide);
+        self.show_search_match(current);
+    }
+
+    /// Removes the search highlights.
+    pub fn clear_search(&mut self) {
+        self.search.clear();
+        self.highlight_search();
+    }
+
+    pub fn next_search_match(&mut self) {
+        let current = self.search.next_match(self.active_slide);
+        self.show_search_match(current);
+    }
+
+    pub fn previous_search_match(&mut self) {
+        let current = self.search.previous_match(self.active_slide);
+        self.show_search_match(current);
+    }
+
+    /// Marks the match, given as slide and match on the slide, as the
+    /// current one and goes to its slide.
+    fn show_search_match(&mut self, current: Option<(usize, usize)>) {
+        self.overview.invalidate_all();
+        for (index, slide) in self.slides.iter_mut().enumerate() {
+            slide.select_highlight(
+                current
+                    .filter(|(current_slide, _)| *current_slide == index)
+                    .map(|(_, current_match)| current_match),
+            );
+        }
+        if let Some((slide, _)) = current {
+            self.goto(slide + 1);
+        }
+    }
+
+    pub fn draw_search(&self) {
+        self.search.draw();
+    }
+
+    /// Fades to the color, or back to the slide if the screen is already
+    /// blanked with that color.
+    pub fn toggle_blank_screen(&mut self, color: Color) {
+        self.blank_screen.toggle(color);
+    }
+
+    pub fn is_screen_blanked(&self) -> bool {
+        self.blank_screen.is_blanked()
+    }
+
+    pub fn draw_blank_screen(&self) {
+        self.blank_screen.draw();
+    }
+
+    pub fn toggle_talk_timer(&mut self) {
+        self.talk_timer.toggle_show();
+    }
+
+    pub fn talk_timer(&self) -> &TalkTimer {
+        &self.talk_timer
+    }
+
+    pub fn draw_talk_timer(&self) {
+        self.talk_timer.draw(self.active_slide + 1);
+    }
+
+    fn highlight_search(&mut self) {
+        let mut color = self.theme.search_highlight_color;
+        color.a = Self::SEARCH_HIGHLIGHT_ALPHA;
+        let matches = self
+            .slides
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slide)| {
+                let count = slide.highlight(self.search.query(), color);
+                (count > 0).then_some((index, count))
+            })
+            .collect();
+        self.search.set_matches(matches);
+        self.overview.invalidate_all();
+    }
+
+    /// Renders slides without a thumbnail.
+    fn render_thumbnails(&mut self) {
+        self.overview.check_screen_size();
+        for index in 0..self.slides.len() {
+            if self.overview.has_thumbnail(index) {
+                continue;
+            }
+            let mut camera =
+                Camera2D::from_display_rect(Rect::new(0., 0., screen_width(), screen_height()));
+            camera.render_target = Some(self.overview.thumbnail_target(index));
+            set_camera(&camera);
+            clear_background(self.theme.background_color);
+            self.draw_slide_at(index);
+        }
+        set_default_camera();
+    }
+
+    /// Renders the slide after the current one, for the presenter view.
+    pub fn next_slide_texture(&mut self) -> Option<Texture2D> {
+        let render_target = self
+            .next_render_target
+            .get_or_insert_with(Self::render_target)
+            .clone();
+        let index = self.active_slide + 1;
+        if index >= self.slides.len() {
+            return None;
+        }
+        let mut camera =
+            Camera2D::from_display_rect(Rect::new(0., 0., screen_width(), screen_height()));
+        camera.render_target = Some(render_target.clone());
+        set_camera(&camera);
+        clear_background(self.theme.background_color);
+        self.draw_slide_at(index);
+        set_default_camera();
+        Some(render_target.texture)
     }
 
     pub fn texture(&mut self) -> Texture2D {
@@ -310,32 +892,161 @@ impl Slides {
         self.render_target.texture.clone()
     }
 
+    /// Starts running the focused code block in the background. If the
+    /// focused code block is already running, typed text is sent to it
+    /// instead. Only one code block runs at a time.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn run_code_block(&mut self) {
-        let slide = self.slides.get_mut(self.active_slide).unwrap();
-        if let Some(code_block) = &slide.code_block {
-            let output = match code_block.execute() {
-                Ok(output) => output,
-                Err(err) => err.to_string(),
-            };
-            let code_box = self
-                .code_box_builder
-                .build_draw_box(None, output.to_string());
-            slide.add_code_box(code_box);
+        let Some(slide) = self.slides.get_mut(self.active_slide) else {
+            return;
+        };
+        let code_block_index = slide.focused_code_block_index();
+        if let Some(running_code) = &mut self.running_code {
+            if running_code.slide_index == self.active_slide
+                && running_code.code_block_index == code_block_index
+            {
+                running_code.start_typing();
+            }
+            return;
+        }
+        if let Some(code_block) = slide.focused_code_block_mut() {
+            code_block.start();
+            self.running_code = Some(RunningCode::start(
+                self.active_slide,
+                code_block_index,
+                code_block.code.clone(),
+                self.code_timeout,
+            ));
+        }
+    }
+
+    /// Shows the recorded output of the focused code block, for when the code
+    /// can't be run.
+    pub fn replay_code_block(&mut self) {
+        let Some(slide) = self.slides.get_mut(self.active_slide) else {
+            return;
+        };
+        if let Some(code_block) = slide.focused_code_block_mut() {
+            if let Some(recorded_output) = self.recorded_outputs.find(&code_block.code) {
+                let finished = FinishedRun::Result(recorded_output.result());
+                let code_box = self.code_box_builder.build_finished_box(&finished, 0);
+                code_block.finish(finished, code_box);
+            }
+        }
+    }
+
+    /// Typed text goes to the running code instead of being used as
+    /// shortcuts.
+    pub fn is_reading_code_input(&self) -> bool {
+        self.running_code
+            .as_ref()
+            .is_some_and(RunningCode::is_typing)
+    }
+
+    pub fn add_code_input(&mut self, c: char) {
+        if let Some(running_code) = &mut self.running_code {
+            running_code.push_input(c);
+        }
+    }
+
+    pub fn remove_code_input(&mut self) {
+        if let Some(running_code) = &mut self.running_code {
+            running_code.pop_input();
+        }
+    }
+
+    pub fn send_code_input(&mut self) {
+        if let Some(running_code) = &mut self.running_code {
+            running_code.send_input();
+        }
+    }
+
+    pub fn close_code_input(&mut self) {
+        if let Some(running_code) = &mut self.running_code {
+            running_code.close_input();
+        }
+    }
+
+    /// Typed text is used as shortcuts again, the code keeps running.
+    pub fn stop_code_input(&mut self) {
+        if let Some(running_code) = &mut self.running_code {
+            running_code.stop_typing();
+        }
+    }
+
+    /// Removes the output of the focused code block, unless it is running.
+    pub fn clear_code_output(&mut self) {
+        if let Some(code_block) = self
+            .slides
+            .get_mut(self.active_slide)
+            .and_then(|slide| slide.focused_code_block_mut())
+        {
+            if !code_block.running {
+                code_block.clear_output();
+            }
+        }
+    }
+
+    /// Scrolls the output of the focused code block up, or down with a
+    /// negative number of lines, once the code has finished.
+    pub fn scroll_code_output(&mut self, lines: isize) {
+        let Some(code_block) = self
+            .slides
+            .get_mut(self.active_slide)
+            .and_then(|slide| slide.focused_code_block_mut())
+        else {
+            return;
+        };
+        let Some(finished) = &code_block.finished else {
+            return;
+        };
+        let max_scroll = self.code_box_builder.max_scroll(finished);
+        code_block.scroll = code_block
+            .scroll
+            .saturating_add_signed(lines)
+            .min(max_scroll);
+        code_block.output = Some(
+            self.code_box_builder
+                .build_finished_box(finished, code_block.scroll),
+        );
+    }
+
+    pub fn cancel_code_block(&self) {
+        if let Some(running_code) = &self.running_code {
+            running_code.cancel();
         }
     }
 
     pub fn copy_codeblock(&self) {
         if let Some(slide) = self.current_slide() {
-            if let Some(code) = &slide.code_block {
-                set_clipboard(&code.code());
+            if let Some(code_block) = slide.focused_code_block() {
+                set_clipboard(&code_block.code.code());
             }
         }
     }
 
+    pub fn focus_code_block(&mut self, index: usize) {
+        if let Some(slide) = self.slides.get_mut(self.active_slide) {
+            slide.focus_code_block(index);
+        }
+    }
+
+    pub fn focus_next_code_block(&mut self) {
+        if let Some(slide) = self.slides.get_mut(self.active_slide) {
+            slide.focus_next_code_block();
+        }
+    }
+
     fn draw_slide(&self) {
-        if let Some(slide) = self.current_slide() {
+        self.draw_slide_at(self.active_slide);
+    }
+
+    /// Draws a slide with the progress bar, slide counter and deck title.
+    fn draw_slide_at(&self, index: usize) {
+        if let Some(slide) = self.slides.get(index) {
             slide.draw(self.background.clone());
+            self.chrome
+                .draw(index + 1, self.slides.len(), slide.hidden_chrome);
         }
     }
 
@@ -367,3 +1078,22 @@ impl Slides {
         set_camera(&render_target_camera)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_front_matter() {
+        let front_matter = Slides::front_matter("---\ntitle: Deck\nauthor: \"Me\"\n---\n# Slide\n");
+        assert_eq!(front_matter.len(), 2);
+        assert_eq!(front_matter["title"], "Deck");
+        assert_eq!(front_matter["author"], "Me");
+    }
+
+    #[test]
+    fn only_reads_front_matter_at_the_start() {
+        assert!(Slides::front_matter("# Slide\n\n---\ntitle: Not a header\n---\n").is_empty());
+        assert!(Slides::front_matter("# Slide\n").is_empty());
+    }
+}
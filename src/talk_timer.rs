@@ -0,0 +1,168 @@
+use crate::prelude::Duration;
+use macroquad::{
+    color::{Color, colors::WHITE},
+    shapes::draw_rectangle,
+    text::{draw_text, measure_text},
+    window::screen_width,
+};
+
+/// Time spent on the talk, and the time left of the talk length. The timer is
+/// tinted when the talk is behind the time budgets of the slides, or has run
+/// past its length. The presenter view and console show the same timer.
+pub struct TalkTimer {
+    elapsed: Duration,
+    talk_length: Option<Duration>,
+    time_budgets: Vec<Option<Duration>>,
+    pub showing: bool,
+}
+
+impl TalkTimer {
+    const BACKGROUND_COLOR: Color = Color::new(0.1, 0.1, 0.1, 0.8);
+    const FONT_COLOR: Color = WHITE;
+    const BEHIND_COLOR: Color = Color::new(1., 0.65, 0., 1.);
+    const OVERTIME_COLOR: Color = Color::new(0.95, 0.3, 0.3, 1.);
+    const MARGIN: f32 = 30.;
+    const PADDING: f32 = 20.;
+    const FONT_SIZE: f32 = 40.;
+
+    /// Times are in seconds. Each slide can have a time budget.
+    pub fn new(talk_length: Option<Duration>, time_budgets: Vec<Option<Duration>>) -> Self {
+        Self {
+            elapsed: 0.,
+            talk_length,
+            time_budgets,
+            showing: false,
+        }
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        self.elapsed += delta;
+    }
+
+    pub fn toggle_show(&mut self) {
+        self.showing = !self.showing;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Behind when more time has passed than the budgets of all slides up to
+    /// and including the current one. Slides before the first slide with a
+    /// budget are never behind.
+    pub fn is_behind(&self, slide_number: usize) -> bool {
+        let planned: Duration = self.time_budgets.iter().take(slide_number).flatten().sum();
+        planned > 0. && self.elapsed > planned
+    }
+
+    pub fn is_overtime(&self) -> bool {
+        self.talk_length
+            .is_some_and(|talk_length| self.elapsed > talk_length)
+    }
+
+    pub fn draw(&self, slide_number: usize) {
+        if !self.showing {
+            return;
+        }
+        let text = self.text();
+        let color = self.color(slide_number);
+        let dimensions = measure_text(&text, None, Self::FONT_SIZE as u16, 1.);
+        let width = dimensions.width + Self::PADDING * 2.;
+        let height = Self::FONT_SIZE + Self::PADDING * 2.;
+        let hpos = screen_width() - width - Self::MARGIN;
+        draw_rectangle(hpos, Self::MARGIN, width, height, Self::BACKGROUND_COLOR);
+        draw_text(
+            &text,
+            hpos + Self::PADDING,
+            Self::MARGIN + Self::PADDING + dimensions.offset_y,
+            Self::FONT_SIZE,
+            color,
+        );
+    }
+
+    /// Color of the timer, tinted when behind or overtime.
+    pub fn color(&self, slide_number: usize) -> Color {
+        if self.is_overtime() {
+            Self::OVERTIME_COLOR
+        } else if self.is_behind(slide_number) {
+            Self::BEHIND_COLOR
+        } else {
+            Self::FONT_COLOR
+        }
+    }
+
+    /// The elapsed time, followed by the time left or over if there is a
+    /// talk length.
+    pub fn text(&self) -> String {
+        match self.remaining_text() {
+            Some(remaining) => format!("{}  {}", self.elapsed_text(), remaining),
+            None => self.elapsed_text(),
+        }
+    }
+
+    pub fn elapsed_text(&self) -> String {
+        Self::format(self.elapsed)
+    }
+
+    /// Time left of the talk length, or time over it.
+    pub fn remaining_text(&self) -> Option<String> {
+        self.talk_length.map(|talk_length| {
+            if self.elapsed > talk_length {
+                format!("{} over", Self::format(self.elapsed - talk_length))
+            } else {
+                format!("{} left", Self::format(talk_length - self.elapsed))
+            }
+        })
+    }
+
+    fn format(seconds: Duration) -> String {
+        let seconds = seconds as u64;
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timer(elapsed: Duration, budgets: &[Option<Duration>]) -> TalkTimer {
+        let mut talk_timer = TalkTimer::new(None, budgets.to_vec());
+        talk_timer.update(elapsed);
+        talk_timer
+    }
+
+    #[test]
+    fn never_behind_without_budgets() {
+        assert!(!timer(1000., &[None, None]).is_behind(2));
+    }
+
+    #[test]
+    fn behind_when_past_budgets_so_far() {
+        let talk_timer = timer(90., &[Some(60.), Some(60.)]);
+        assert!(talk_timer.is_behind(1));
+        assert!(!talk_timer.is_behind(2));
+    }
+
+    #[test]
+    fn not_behind_before_first_budget() {
+        assert!(!timer(30., &[None, None, Some(60.)]).is_behind(1));
+        assert!(!timer(30., &[None, None, Some(60.)]).is_behind(3));
+        assert!(timer(70., &[None, None, Some(60.)]).is_behind(3));
+    }
+
+    #[test]
+    fn shows_time_left_and_over() {
+        let mut talk_timer = TalkTimer::new(Some(600.), vec![]);
+        talk_timer.update(65.);
+        assert_eq!(talk_timer.text(), "0:01:05  0:08:55 left");
+        assert!(!talk_timer.is_overtime());
+        talk_timer.update(635.);
+        assert_eq!(talk_timer.text(), "0:11:40  0:01:40 over");
+        assert!(talk_timer.is_overtime());
+    }
+}
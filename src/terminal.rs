@@ -0,0 +1,162 @@
+use crate::prelude::TerminalScreen;
+use std::{io, sync::mpsc::Receiver};
+
+#[cfg(target_os = "linux")]
+use std::{
+    fs::File,
+    io::prelude::*,
+    os::unix::{io::FromRawFd, process::CommandExt},
+    process::{Child, Command, Stdio},
+    sync::mpsc::channel,
+    thread,
+};
+
+/// A program running in a pseudo terminal, usually a shell. Output is read on
+/// a background thread and shown on the terminal screen.
+pub struct Terminal {
+    pub slide_index: usize,
+    pub draw_box_index: usize,
+    screen: TerminalScreen,
+    receiver: Receiver<Vec<u8>>,
+    #[cfg(target_os = "linux")]
+    master: File,
+    #[cfg(target_os = "linux")]
+    child: Child,
+}
+
+impl Terminal {
+    #[cfg(target_os = "linux")]
+    const READ_BUFFER_SIZE: usize = 4096;
+
+    /// The command is split on whitespace, the shell of the user is started if
+    /// it is empty.
+    #[cfg(target_os = "linux")]
+    pub fn start(
+        slide_index: usize,
+        draw_box_index: usize,
+        command: &str,
+        rows: usize,
+        columns: usize,
+    ) -> io::Result<Self> {
+        let (master, slave) = Self::open_pty(rows, columns)?;
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+        let mut args = command.split_whitespace();
+        let mut command = Command::new(args.next().unwrap_or(&shell));
+        command
+            .args(args)
+            .env("TERM", "vt100")
+            .env("COLUMNS", columns.to_string())
+            .env("LINES", rows.to_string())
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+        // Makes the pseudo terminal the controlling terminal of the program,
+        // so that job control and Ctrl-C work.
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        let mut reader = master.try_clone()?;
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            let mut buffer = [0; Self::READ_BUFFER_SIZE];
+            while let Ok(length) = reader.read(&mut buffer) {
+                if length == 0 || sender.send(buffer[..length].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            slide_index,
+            draw_box_index,
+            screen: TerminalScreen::new(rows, columns),
+            receiver,
+            master,
+            child,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn start(
+        _slide_index: usize,
+        _draw_box_index: usize,
+        _command: &str,
+        _rows: usize,
+        _columns: usize,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Terminals are only supported on Linux",
+        ))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn open_pty(rows: usize, columns: usize) -> io::Result<(File, File)> {
+        let mut master = 0;
+        let mut slave = 0;
+        let size = libc::winsize {
+            ws_row: rows as u16,
+            ws_col: columns as u16,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let result = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &size,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Neither side should leak into other processes. The program still
+        // gets the slave side as its stdio, as copying it there clears the flag
+        for fd in [master, slave] {
+            unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+        }
+        unsafe { Ok((File::from_raw_fd(master), File::from_raw_fd(slave))) }
+    }
+
+    pub fn screen(&self) -> &TerminalScreen {
+        &self.screen
+    }
+
+    /// Shows output received since the last call, returns true if there was
+    /// any.
+    pub fn update(&mut self) -> bool {
+        let mut updated = false;
+        while let Ok(bytes) = self.receiver.try_recv() {
+            self.screen.write(&bytes);
+            updated = true;
+        }
+        updated
+    }
+
+    /// Sends key presses to the program.
+    pub fn send(&mut self, bytes: &[u8]) {
+        #[cfg(target_os = "linux")]
+        {
+            let _ = self.master.write_all(bytes);
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = bytes;
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
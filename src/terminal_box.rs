@@ -0,0 +1,41 @@
+use crate::prelude::*;
+
+/// A terminal on a slide, declared with a `terminal` code block. The code
+/// block contains the command to run, or is empty to run the shell of the
+/// user. The box shows the command until the terminal is started.
+#[derive(Clone)]
+pub struct TerminalBox {
+    command: String,
+    code_box: CodeBox,
+    pub focused: bool,
+}
+
+impl TerminalBox {
+    pub fn new(command: String, code_box: CodeBox) -> Self {
+        Self {
+            command,
+            code_box,
+            focused: false,
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn code_box(&self) -> &CodeBox {
+        &self.code_box
+    }
+
+    pub fn set_code_box(&mut self, code_box: CodeBox) {
+        self.code_box = code_box;
+    }
+
+    pub fn draw(&self, hpos: Hpos, vpos: Vpos) -> Vpos {
+        self.code_box.draw(hpos, vpos)
+    }
+
+    pub fn width_with_padding(&self) -> Width {
+        self.code_box.width_with_padding()
+    }
+}
@@ -0,0 +1,381 @@
+use crate::prelude::{AnsiSegment, AnsiStyle};
+
+type Cell = (char, AnsiStyle);
+
+#[derive(Clone, Copy, PartialEq)]
+enum ParseState {
+    Text,
+    Escape,
+    ControlSequence,
+    OperatingSystemCommand,
+    CharacterSet,
+}
+
+/// The screen of a terminal with a fixed size, handling the subset of VT100
+/// control sequences used by shells and most command line programs: cursor
+/// movement, erasing, scrolling and SGR colors. Unsupported sequences are
+/// ignored.
+pub struct TerminalScreen {
+    rows: usize,
+    columns: usize,
+    cells: Vec<Vec<Cell>>,
+    row: usize,
+    column: usize,
+    saved_cursor: (usize, usize),
+    cursor_visible: bool,
+    style: AnsiStyle,
+    state: ParseState,
+    sequence: String,
+    pending: Vec<u8>,
+}
+
+impl TerminalScreen {
+    const ESCAPE: char = '\u{1b}';
+    const BELL: char = '\u{7}';
+    const TAB_WIDTH: usize = 8;
+
+    pub fn new(rows: usize, columns: usize) -> Self {
+        let rows = rows.max(1);
+        let columns = columns.max(1);
+        Self {
+            rows,
+            columns,
+            cells: vec![Self::blank_row(columns); rows],
+            row: 0,
+            column: 0,
+            saved_cursor: (0, 0),
+            cursor_visible: true,
+            style: AnsiStyle::default(),
+            state: ParseState::Text,
+            sequence: String::new(),
+            pending: vec![],
+        }
+    }
+
+    /// Handles output from the program. Incomplete UTF-8 characters are kept
+    /// until the next write.
+    pub fn write(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            _ => self.pending.len(),
+        };
+        let text = String::from_utf8_lossy(&self.pending[..valid]).to_string();
+        self.pending.drain(..valid);
+        for c in text.chars() {
+            self.read(c);
+        }
+    }
+
+    /// The lines of the screen as styled segments. The cursor is shown as an
+    /// underlined cell.
+    pub fn lines(&self, show_cursor: bool) -> Vec<Vec<AnsiSegment>> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(row, cells)| {
+                let mut segments: Vec<AnsiSegment> = vec![];
+                for (column, (c, style)) in cells.iter().enumerate() {
+                    let mut style = *style;
+                    if show_cursor
+                        && self.cursor_visible
+                        && row == self.row
+                        && column == self.column.min(self.columns - 1)
+                    {
+                        style.underline = true;
+                    }
+                    match segments.last_mut() {
+                        Some(segment) if segment.style == style => segment.text.push(*c),
+                        _ => segments.push(AnsiSegment {
+                            text: c.to_string(),
+                            style,
+                        }),
+                    }
+                }
+                segments
+            })
+            .collect()
+    }
+
+    fn blank_row(columns: usize) -> Vec<Cell> {
+        vec![(' ', AnsiStyle::default()); columns]
+    }
+
+    fn read(&mut self, c: char) {
+        match self.state {
+            ParseState::Text => self.read_text(c),
+            ParseState::Escape => self.read_escape(c),
+            ParseState::ControlSequence => {
+                if ('\u{40}'..='\u{7e}').contains(&c) {
+                    let sequence = std::mem::take(&mut self.sequence);
+                    self.control_sequence(&sequence, c);
+                    self.state = ParseState::Text;
+                } else {
+                    self.sequence.push(c);
+                }
+            }
+            ParseState::OperatingSystemCommand => match c {
+                Self::BELL => self.state = ParseState::Text,
+                Self::ESCAPE => self.state = ParseState::Escape,
+                _ => (),
+            },
+            ParseState::CharacterSet => self.state = ParseState::Text,
+        }
+    }
+
+    fn read_text(&mut self, c: char) {
+        match c {
+            Self::ESCAPE => self.state = ParseState::Escape,
+            '\r' => self.column = 0,
+            '\n' | '\u{b}' | '\u{c}' => self.line_feed(),
+            '\u{8}' => self.column = self.column.min(self.columns - 1).saturating_sub(1),
+            '\t' => {
+                self.column =
+                    ((self.column / Self::TAB_WIDTH + 1) * Self::TAB_WIDTH).min(self.columns - 1)
+            }
+            c if c.is_control() => (),
+            c => self.print(c),
+        }
+    }
+
+    fn read_escape(&mut self, c: char) {
+        self.state = ParseState::Text;
+        match c {
+            '[' => {
+                self.sequence.clear();
+                self.state = ParseState::ControlSequence;
+            }
+            ']' => self.state = ParseState::OperatingSystemCommand,
+            '(' | ')' => self.state = ParseState::CharacterSet,
+            '7' => self.saved_cursor = (self.row, self.column),
+            '8' => (self.row, self.column) = self.saved_cursor,
+            'D' => self.line_feed(),
+            'E' => {
+                self.column = 0;
+                self.line_feed();
+            }
+            'M' => self.reverse_line_feed(),
+            'c' => *self = Self::new(self.rows, self.columns),
+            _ => (),
+        }
+    }
+
+    fn control_sequence(&mut self, sequence: &str, command: char) {
+        if let Some(private) = sequence.strip_prefix('?') {
+            if private == "25" {
+                self.cursor_visible = command == 'h';
+            }
+            return;
+        }
+        let params = sequence
+            .split(';')
+            .map(|param| param.parse::<usize>().unwrap_or(0))
+            .collect::<Vec<usize>>();
+        let param = |index: usize| params.get(index).copied().unwrap_or(0);
+        let count = param(0).max(1);
+        match command {
+            'A' => self.row = self.row.saturating_sub(count),
+            'B' => self.row = (self.row + count).min(self.rows - 1),
+            'C' => self.column = (self.column + count).min(self.columns - 1),
+            'D' => self.column = self.column.min(self.columns - 1).saturating_sub(count),
+            'E' => {
+                self.row = (self.row + count).min(self.rows - 1);
+                self.column = 0;
+            }
+            'F' => {
+                self.row = self.row.saturating_sub(count);
+                self.column = 0;
+            }
+            'G' => self.column = (count - 1).min(self.columns - 1),
+            'd' => self.row = (count - 1).min(self.rows - 1),
+            'H' | 'f' => {
+                self.row = (param(0).max(1) - 1).min(self.rows - 1);
+                self.column = (param(1).max(1) - 1).min(self.columns - 1);
+            }
+            'J' => self.erase_display(param(0)),
+            'K' => self.erase_line(param(0)),
+            'L' => {
+                for _ in 0..count.min(self.rows - self.row) {
+                    self.cells.pop();
+                    self.cells.insert(self.row, Self::blank_row(self.columns));
+                }
+            }
+            'M' => {
+                for _ in 0..count.min(self.rows - self.row) {
+                    self.cells.remove(self.row);
+                    self.cells.push(Self::blank_row(self.columns));
+                }
+            }
+            'P' => {
+                let column = self.column.min(self.columns - 1);
+                let line = &mut self.cells[self.row];
+                for _ in 0..count.min(self.columns - column) {
+                    line.remove(column);
+                    line.push((' ', AnsiStyle::default()));
+                }
+            }
+            '@' => {
+                let column = self.column.min(self.columns - 1);
+                let line = &mut self.cells[self.row];
+                for _ in 0..count.min(self.columns - column) {
+                    line.pop();
+                    line.insert(column, (' ', AnsiStyle::default()));
+                }
+            }
+            'X' => {
+                let column = self.column.min(self.columns - 1);
+                let end = (column + count).min(self.columns);
+                self.cells[self.row][column..end].fill((' ', AnsiStyle::default()));
+            }
+            's' => self.saved_cursor = (self.row, self.column),
+            'u' => (self.row, self.column) = self.saved_cursor,
+            'm' => {
+                let params = sequence
+                    .split(';')
+                    .filter(|param| !param.is_empty())
+                    .map(|param| param.parse().unwrap_or(0))
+                    .collect::<Vec<u16>>();
+                self.style.apply_sgr(&params);
+            }
+            _ => (),
+        }
+    }
+
+    /// Characters written past the last column wrap to the next line.
+    fn print(&mut self, c: char) {
+        if self.column >= self.columns {
+            self.column = 0;
+            self.line_feed();
+        }
+        self.cells[self.row][self.column] = (c, self.style);
+        self.column += 1;
+    }
+
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.rows {
+            self.row += 1;
+        } else {
+            self.cells.remove(0);
+            self.cells.push(Self::blank_row(self.columns));
+        }
+    }
+
+    fn reverse_line_feed(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+        } else {
+            self.cells.pop();
+            self.cells.insert(0, Self::blank_row(self.columns));
+        }
+    }
+
+    fn erase_display(&mut self, mode: usize) {
+        match mode {
+            0 => {
+                self.erase_line(0);
+                for line in self.cells.iter_mut().skip(self.row + 1) {
+                    *line = Self::blank_row(self.columns);
+                }
+            }
+            1 => {
+                self.erase_line(1);
+                for line in self.cells.iter_mut().take(self.row) {
+                    *line = Self::blank_row(self.columns);
+                }
+            }
+            _ => self.cells = vec![Self::blank_row(self.columns); self.rows],
+        }
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let column = self.column.min(self.columns);
+        let line = &mut self.cells[self.row];
+        let range = match mode {
+            0 => column..self.columns,
+            1 => 0..(column + 1).min(self.columns),
+            _ => 0..self.columns,
+        };
+        line[range].fill((' ', AnsiStyle::default()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(screen: &TerminalScreen) -> Vec<String> {
+        screen
+            .lines(false)
+            .iter()
+            .map(|segments| {
+                segments
+                    .iter()
+                    .map(|segment| segment.text.as_str())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wraps_long_lines() {
+        let mut screen = TerminalScreen::new(3, 5);
+        screen.write(b"hello world");
+        assert_eq!(texts(&screen), vec!["hello", " worl", "d"]);
+    }
+
+    #[test]
+    fn scrolls_at_the_bottom() {
+        let mut screen = TerminalScreen::new(2, 10);
+        screen.write(b"one\r\ntwo\r\nthree");
+        assert_eq!(texts(&screen), vec!["two", "three"]);
+    }
+
+    #[test]
+    fn moves_cursor_and_erases() {
+        let mut screen = TerminalScreen::new(2, 10);
+        screen.write(b"abcdef\x1b[3D\x1b[K");
+        screen.write(b"\x1b[2;4HX");
+        assert_eq!(texts(&screen), vec!["abc", "   X"]);
+        screen.write(b"\x1b[2J");
+        assert_eq!(texts(&screen), vec!["", ""]);
+    }
+
+    #[test]
+    fn applies_colors() {
+        let mut screen = TerminalScreen::new(1, 10);
+        screen.write(b"\x1b[31mred\x1b[0m ok");
+        let lines = screen.lines(false);
+        assert_eq!(lines[0][0].text, "red");
+        assert_eq!(
+            lines[0][0].style.foreground,
+            Some(macroquad::prelude::Color::from_rgba(205, 49, 49, 255))
+        );
+        assert_eq!(lines[0][1].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn keeps_characters_split_between_writes() {
+        let mut screen = TerminalScreen::new(1, 5);
+        let bytes = "é".as_bytes();
+        screen.write(&bytes[..1]);
+        screen.write(&bytes[1..]);
+        assert_eq!(texts(&screen), vec!["é"]);
+    }
+
+    #[test]
+    fn shows_cursor_unless_hidden() {
+        let mut screen = TerminalScreen::new(1, 3);
+        screen.write(b"a");
+        let lines = screen.lines(true);
+        assert_eq!(lines[0][1].text, " ");
+        assert!(lines[0][1].style.underline);
+        screen.write(b"\x1b[?25l");
+        assert!(
+            screen.lines(true)[0]
+                .iter()
+                .all(|segment| !segment.style.underline)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use macroquad::prelude::*;
+use std::ops::{Range, RangeTo};
 
 #[derive(Clone)]
 pub struct TextBox {
@@ -54,6 +55,15 @@ impl TextBox {
                 DrawAlignment::right => inner_hpos + self.width() - line.width,
                 DrawAlignment::center => inner_hpos + self.width() / 2. - line.width / 2.,
             };
+            if let Some(color) = line.background_color {
+                draw_rectangle(
+                    hpos,
+                    new_position,
+                    self.width_with_padding(),
+                    line.height,
+                    color,
+                );
+            }
             new_position = line.draw(line_hpos, new_position, self.offset_y);
         }
         vpos + self.height_with_margin()
@@ -71,6 +81,25 @@ impl TextBox {
         }
     }
 
+    /// Highlights all places where the query is found, ignoring ASCII case.
+    /// An empty query removes the highlights. Returns the number of matches.
+    pub fn highlight(&mut self, query: &str, color: Color) -> usize {
+        self.lines
+            .iter_mut()
+            .map(|line| line.highlight(query, color))
+            .sum()
+    }
+
+    /// Marks the highlight with the index, counted from the first line, as
+    /// the current match. Returns the index left for the boxes after this
+    /// one, or None if the match is in this box. None removes the mark.
+    pub fn select_highlight(&mut self, mut index: Option<usize>) -> Option<usize> {
+        for line in self.lines.iter_mut() {
+            index = line.select_highlight(index);
+        }
+        index
+    }
+
     pub fn background_color(&self) -> Option<Color> {
         self.background_color
     }
@@ -102,10 +131,16 @@ pub struct TextLine {
     height: Height,
     offset_y: Vpos,
     align: DrawAlignment,
+    background_color: Option<Color>,
+    highlights: Vec<Range<usize>>,
+    current_highlight: Option<usize>,
+    highlight_color: Color,
     partials: Vec<TextPartial>,
 }
 
 impl TextLine {
+    const CURRENT_HIGHLIGHT_THICKNESS: f32 = 3.;
+
     pub fn new(align: DrawAlignment, partials: Vec<TextPartial>) -> Self {
         let mut width: Width = 0.;
         let mut height: Height = 0.;
@@ -120,17 +155,93 @@ impl TextLine {
             height,
             offset_y,
             align,
+            background_color: None,
+            highlights: vec![],
+            current_highlight: None,
+            highlight_color: BLANK,
             partials,
         }
     }
 
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = Some(color);
+    }
+
+    pub fn text(&self) -> String {
+        self.partials
+            .iter()
+            .map(|partial| partial.text.as_str())
+            .collect()
+    }
+
+    fn highlight(&mut self, query: &str, color: Color) -> usize {
+        self.highlights.clear();
+        self.current_highlight = None;
+        self.highlight_color = color;
+        if query.is_empty() {
+            return 0;
+        }
+        let text = self.text().to_ascii_lowercase();
+        let query = query.to_ascii_lowercase();
+        self.highlights = text
+            .match_indices(&query)
+            .map(|(start, found)| start..start + found.len())
+            .collect();
+        self.highlights.len()
+    }
+
+    fn select_highlight(&mut self, index: Option<usize>) -> Option<usize> {
+        self.current_highlight = None;
+        let index = index?;
+        if index < self.highlights.len() {
+            self.current_highlight = Some(index);
+            None
+        } else {
+            Some(index - self.highlights.len())
+        }
+    }
+
     fn draw(&self, start_hpos: Hpos, vpos: Vpos, offset_y: Vpos) -> Vpos {
+        self.draw_highlights(start_hpos, vpos);
         let mut hpos = start_hpos;
         for partial in &self.partials {
             hpos = partial.draw(hpos, vpos, offset_y);
         }
         vpos + self.height
     }
+
+    /// Draws the highlights behind the text, a highlight can span several
+    /// partials. The current match also gets an outline.
+    fn draw_highlights(&self, start_hpos: Hpos, vpos: Vpos) {
+        let mut hpos = start_hpos;
+        let mut partial_start = 0;
+        for partial in &self.partials {
+            let partial_end = partial_start + partial.text.len();
+            for (index, highlight) in self.highlights.iter().enumerate() {
+                let start = highlight.start.max(partial_start);
+                let end = highlight.end.min(partial_end);
+                if start < end {
+                    let left = hpos + partial.text_width(..start - partial_start);
+                    let right = hpos + partial.text_width(..end - partial_start);
+                    draw_rectangle(left, vpos, right - left, self.height, self.highlight_color);
+                    if self.current_highlight == Some(index) {
+                        let mut outline_color = self.highlight_color;
+                        outline_color.a = 1.;
+                        draw_rectangle_lines(
+                            left,
+                            vpos,
+                            right - left,
+                            self.height,
+                            Self::CURRENT_HIGHLIGHT_THICKNESS,
+                            outline_color,
+                        );
+                    }
+                }
+            }
+            hpos += partial.width;
+            partial_start = partial_end;
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -141,6 +252,7 @@ pub struct TextPartial {
     font: Font,
     font_size: FontSize,
     offset_y: Vpos,
+    underline: bool,
     text: String,
 }
 
@@ -160,10 +272,19 @@ impl TextPartial {
             font,
             font_size,
             offset_y: dimensions.offset_y,
+            underline: false,
             text: text.to_owned(),
         }
     }
 
+    pub fn set_underline(&mut self, underline: bool) {
+        self.underline = underline;
+    }
+
+    fn text_width(&self, range: RangeTo<usize>) -> Width {
+        measure_text(&self.text[range], Some(&self.font), self.font_size, 1.).width
+    }
+
     fn draw(&self, hpos: Hpos, vpos: Vpos, offset_y: Vpos) -> Vpos {
         draw_text_ex(
             &self.text,
@@ -176,6 +297,17 @@ impl TextPartial {
                 ..Default::default()
             },
         );
+        if self.underline {
+            let underline_vpos = vpos + offset_y + self.font_size as Vpos * 0.15;
+            draw_line(
+                hpos,
+                underline_vpos,
+                hpos + self.width,
+                underline_vpos,
+                1.,
+                self.color,
+            );
+        }
         hpos + self.width
     }
 }
@@ -35,6 +35,36 @@ pub struct Theme {
     pub code_background_color: Color,
     pub code_theme: String,
     pub code_tab_width: usize,
+    #[nserde(proxy = "HexColor")]
+    pub code_focus_color: Color,
+    #[nserde(proxy = "HexColor")]
+    pub diff_added_background_color: Color,
+    #[nserde(proxy = "HexColor")]
+    pub diff_removed_background_color: Color,
+    #[nserde(proxy = "HexColor")]
+    pub output_background_color: Color,
+    #[nserde(proxy = "HexColor")]
+    pub output_text_color: Color,
+    pub output_max_lines: usize,
+    #[nserde(proxy = "HexColor")]
+    pub output_error_color: Color,
+    #[nserde(proxy = "HexColor")]
+    pub output_failure_color: Color,
+    #[nserde(proxy = "HexColor")]
+    pub search_highlight_color: Color,
+    pub terminal_rows: usize,
+    pub terminal_columns: usize,
+    pub progress_bar: bool,
+    pub progress_bar_position: VerticalPosition,
+    #[nserde(proxy = "HexColor")]
+    pub progress_bar_color: Color,
+    pub progress_bar_thickness: f32,
+    pub slide_counter: bool,
+    pub slide_counter_position: Corner,
+    pub deck_title: bool,
+    pub chrome_font_size: FontSize,
+    #[nserde(proxy = "HexColor")]
+    pub chrome_text_color: Color,
     pub bullet: String,
     pub shader: bool,
     pub transition: Option<Transitioning>,
@@ -67,6 +97,26 @@ impl Default for Theme {
             code_background_color: Color::from_rgba(0, 43, 54, 255),
             code_theme: "Solarized (dark)".to_string(),
             code_tab_width: 4,
+            code_focus_color: Color::from_rgba(255, 200, 0, 255),
+            diff_added_background_color: Color::from_rgba(35, 85, 40, 255),
+            diff_removed_background_color: Color::from_rgba(110, 35, 35, 255),
+            output_background_color: Color::from_rgba(30, 30, 30, 255),
+            output_text_color: Color::from_rgba(229, 229, 229, 255),
+            output_max_lines: 15,
+            output_error_color: Color::from_rgba(241, 76, 76, 255),
+            output_failure_color: Color::from_rgba(110, 35, 35, 255),
+            search_highlight_color: Color::from_rgba(255, 200, 0, 255),
+            terminal_rows: 15,
+            terminal_columns: 80,
+            progress_bar: false,
+            progress_bar_position: VerticalPosition::bottom,
+            progress_bar_color: Color::from_rgba(177, 156, 217, 255),
+            progress_bar_thickness: 6.,
+            slide_counter: false,
+            slide_counter_position: Corner::bottom_right,
+            deck_title: false,
+            chrome_font_size: 24,
+            chrome_text_color: Color::from_rgba(204, 204, 204, 255),
             bullet: "• ".to_string(),
             shader: true,
             transition: Some(Transitioning::swiperight),